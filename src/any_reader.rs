@@ -0,0 +1,327 @@
+//! Reading Verilog hex, Intel HEX or Motorola S-record input into the same records.
+//!
+//! # Reading example:
+//!
+//! ```ignore
+//! for record in crate::AnyReader::new(text) {
+//!     std::println!("{}", record?);
+//! }
+//! ```
+
+use core::iter::Enumerate;
+use core::str::Lines;
+
+use crate::{Addr, DataType, ParseError, Reader, ReaderError, Record};
+
+/// Input file format, see [`detect_format`].
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Format {
+    /// `$readmemh` text, as written by `objcopy -O verilog`.
+    VerilogHex,
+    /// Intel HEX, lines like `:10010000...`.
+    IntelHex,
+    /// Motorola S-record, lines like `S1130000...`.
+    Srec,
+}
+
+/// Guess the format of `text` from its first non-blank line.
+pub fn detect_format(text: &str) -> Format {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty());
+    match line.map(str::as_bytes) {
+        Some([b':', ..]) => Format::IntelHex,
+        Some([b'S', digit, ..]) if digit.is_ascii_digit() => Format::Srec,
+        _ => Format::VerilogHex,
+    }
+}
+
+/// Reader of any [`Format`] producing the same records as [`Reader`].
+///
+/// Intel HEX and S-record data is returned as [`DataType::U8`] with a
+/// [`Record::NewAddress`] before every discontinuity, reader options do not apply.
+/// Start address and header records are skipped.
+pub struct AnyReader<'a> {
+    inner: Inner<'a>,
+}
+
+enum Inner<'a> {
+    VerilogHex(Reader<'a>),
+    Lines(LineReader<'a>),
+}
+
+impl<'a> AnyReader<'a> {
+    /// Create a new reader of `text` in the format found by [`detect_format`].
+    pub fn new(text: &'a str) -> Self {
+        AnyReader::new_with_format(text, detect_format(text))
+    }
+
+    /// Create a new reader of `text` in `format`.
+    pub fn new_with_format(text: &'a str, format: Format) -> Self {
+        let inner = match format {
+            Format::VerilogHex => Inner::VerilogHex(Reader::new(text)),
+            format => Inner::Lines(LineReader {
+                format,
+                lines: text.lines().enumerate(),
+                current_addr: 0,
+                base: 0,
+                data: "",
+                finished: false,
+            }),
+        };
+        AnyReader { inner }
+    }
+
+    /// Returns format of the input.
+    pub fn format(&self) -> Format {
+        match &self.inner {
+            Inner::VerilogHex(_) => Format::VerilogHex,
+            Inner::Lines(reader) => reader.format,
+        }
+    }
+}
+
+impl<'a> Iterator for AnyReader<'a> {
+    type Item = Result<Record<'a>, ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::VerilogHex(reader) => reader.next(),
+            Inner::Lines(reader) => reader.next(),
+        }
+    }
+}
+
+/// One parsed Intel HEX or S-record line.
+enum Line<'a> {
+    /// Hex digits of bytes at `addr`.
+    Data {
+        addr: Addr,
+        hex: &'a str,
+    },
+    /// Intel HEX extended address.
+    Base(Addr),
+    End,
+    Skip,
+}
+
+/// Reader of line-based formats.
+struct LineReader<'a> {
+    format: Format,
+    lines: Enumerate<Lines<'a>>,
+    /// Address of the next byte.
+    current_addr: Addr,
+    /// Intel HEX extended address.
+    base: Addr,
+    /// Hex digits of the rest of the current data line.
+    data: &'a str,
+    finished: bool,
+}
+
+impl<'a> LineReader<'a> {
+    fn parse_line(&self, line: &'a str) -> Result<Line<'a>, ReaderError> {
+        match self.format {
+            Format::IntelHex => parse_intel_hex(line, self.base),
+            _ => parse_srec(line),
+        }
+    }
+}
+
+impl<'a> Iterator for LineReader<'a> {
+    type Item = Result<Record<'a>, ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            if let Some(digits) = self.data.get(..2) {
+                self.data = &self.data[2..];
+                let addr = self.current_addr;
+                self.current_addr += 1;
+                let value = DataType::U8(hex_byte(digits).unwrap_or_default());
+                return Some(Ok(Record::Data { addr, value }));
+            }
+            let Some((index, line)) = self.lines.next() else {
+                self.finished = true;
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match self.parse_line(line) {
+                Ok(Line::Data { addr, hex }) => {
+                    self.data = hex;
+                    if addr != self.current_addr && !hex.is_empty() {
+                        self.current_addr = addr;
+                        return Some(Ok(Record::NewAddress(addr)));
+                    }
+                }
+                Ok(Line::Base(base)) => self.base = base,
+                Ok(Line::End) => {
+                    self.finished = true;
+                    return Some(Ok(Record::EndOfFile));
+                }
+                Ok(Line::Skip) => {}
+                Err(kind) => {
+                    self.finished = true;
+                    return Some(Err(ParseError {
+                        kind,
+                        line: index + 1,
+                        column: 1,
+                        token: line,
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parse two hex digits.
+fn hex_byte(digits: &str) -> Result<u8, ReaderError> {
+    if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err(ReaderError::BadNumberConversion);
+    }
+    u8::from_str_radix(digits, 16).map_err(|_| ReaderError::BadNumberConversion)
+}
+
+/// Returns number of bytes in `hex` and their sum, checking that the digits are valid.
+fn byte_sum(hex: &str) -> Result<(usize, u8), ReaderError> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err(ReaderError::InvalidSyntax);
+    }
+    let mut sum = 0u8;
+    for index in (0..hex.len()).step_by(2) {
+        sum = sum.wrapping_add(hex_byte(&hex[index..index + 2])?);
+    }
+    Ok((hex.len() / 2, sum))
+}
+
+/// Returns big-endian value of the first `len` bytes of `hex` checked by [`byte_sum`].
+fn hex_value(hex: &str, len: usize) -> Addr {
+    (0..len).fold(0, |value, index| {
+        let byte = hex_byte(&hex[2 * index..2 * index + 2]).unwrap_or_default();
+        (value << 8) | Addr::from(byte)
+    })
+}
+
+/// Parse Intel HEX `line`, `base` is the current extended address.
+fn parse_intel_hex(line: &str, base: Addr) -> Result<Line<'_>, ReaderError> {
+    let hex = line.strip_prefix(':').ok_or(ReaderError::InvalidSyntax)?;
+    let (count, sum) = byte_sum(hex)?;
+    if count < 5 || usize::from(hex_byte(&hex[..2])?) + 5 != count {
+        return Err(ReaderError::InvalidSyntax);
+    }
+    if sum != 0 {
+        return Err(ReaderError::BadChecksum);
+    }
+    let data = &hex[8..hex.len() - 2];
+    match hex_byte(&hex[6..8])? {
+        0x00 => Ok(Line::Data {
+            addr: base + hex_value(&hex[2..6], 2),
+            hex: data,
+        }),
+        0x01 => Ok(Line::End),
+        0x02 if data.len() == 4 => Ok(Line::Base(hex_value(data, 2) << 4)),
+        0x04 if data.len() == 4 => Ok(Line::Base(hex_value(data, 2) << 16)),
+        0x03 | 0x05 => Ok(Line::Skip),
+        _ => Err(ReaderError::InvalidSyntax),
+    }
+}
+
+/// Parse Motorola S-record `line`.
+fn parse_srec(line: &str) -> Result<Line<'_>, ReaderError> {
+    let hex = line.strip_prefix('S').ok_or(ReaderError::InvalidSyntax)?;
+    let (kind, hex) = hex.split_at_checked(1).ok_or(ReaderError::InvalidSyntax)?;
+    let (count, sum) = byte_sum(hex)?;
+    if count < 2 || usize::from(hex_byte(&hex[..2])?) + 1 != count {
+        return Err(ReaderError::InvalidSyntax);
+    }
+    if sum != 0xFF {
+        return Err(ReaderError::BadChecksum);
+    }
+    let addr_len = match kind {
+        "0" | "5" | "6" => return Ok(Line::Skip),
+        "1" => 2,
+        "2" => 3,
+        "3" => 4,
+        "7" | "8" | "9" => return Ok(Line::End),
+        _ => return Err(ReaderError::InvalidSyntax),
+    };
+    if count < addr_len + 2 {
+        return Err(ReaderError::InvalidSyntax);
+    }
+    Ok(Line::Data {
+        addr: hex_value(&hex[2..], addr_len),
+        hex: &hex[2 + 2 * addr_len..hex.len() - 2],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format("\n  :00000001FF\n"), Format::IntelHex);
+        assert_eq!(detect_format("S00600004844521B"), Format::Srec);
+        assert_eq!(detect_format("@81000000\n09"), Format::VerilogHex);
+        assert_eq!(detect_format(crate::TEXT_STR), Format::VerilogHex);
+    }
+
+    #[test]
+    fn test_intel_hex() {
+        let text = ":020000040800F2\n:0300100009A0F351\n:020013002200C9\n:00000001FF\n";
+        let mut reader = AnyReader::new(text);
+        assert_eq!(reader.format(), Format::IntelHex);
+        let records: [_; 8] = core::array::from_fn(|_| reader.next());
+        let data = |addr, value| {
+            Some(Ok(Record::Data {
+                addr,
+                value: DataType::U8(value),
+            }))
+        };
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x08000010))),
+                data(0x08000010, 0x09),
+                data(0x08000011, 0xA0),
+                data(0x08000012, 0xF3),
+                data(0x08000013, 0x22),
+                data(0x08000014, 0x00),
+                Some(Ok(Record::EndOfFile)),
+                None
+            ]
+        );
+
+        let mut reader = AnyReader::new(":0300100009A0F350\n");
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!((error.kind, error.line), (ReaderError::BadChecksum, 1));
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_srec() {
+        let text = "S00600004844521B\nS1050010AABB85\nS2060100200102D5\nS9030000FC\n";
+        let mut reader = AnyReader::new(text);
+        assert_eq!(reader.format(), Format::Srec);
+        let records: [_; 8] = core::array::from_fn(|_| reader.next());
+        let data = |addr, value| {
+            Some(Ok(Record::Data {
+                addr,
+                value: DataType::U8(value),
+            }))
+        };
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x10))),
+                data(0x10, 0xAA),
+                data(0x11, 0xBB),
+                Some(Ok(Record::NewAddress(0x010020))),
+                data(0x010020, 0x01),
+                data(0x010021, 0x02),
+                Some(Ok(Record::EndOfFile)),
+                None
+            ]
+        );
+    }
+}
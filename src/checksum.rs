@@ -0,0 +1,264 @@
+//! Checksums over image data.
+
+use core::fmt;
+
+use crate::{ByteTransform, Reader, ReaderError, ReaderOptions};
+
+/// Prefix of the checksum footer comment emitted by [`crate::Writer`].
+pub(crate) const FOOTER_PREFIX: &str = "// checksum:";
+
+/// Checksum algorithm. Non-exhaustive, as `Sha256` exists only with the `sha2` feature.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ChecksumAlgorithm {
+    /// CRC-32 as used by zlib and Ethernet.
+    #[default]
+    Crc32,
+    /// CRC-16/CCITT-FALSE, polynomial 0x1021 with initial value 0xFFFF.
+    Crc16,
+    /// Wrapping 32-bit sum of bytes.
+    Sum32,
+    /// SHA-256 digest, requires the `sha2` feature.
+    #[cfg(feature = "sha2")]
+    Sha256,
+}
+
+/// Checksum or digest value, see [`Checksum::digest`].
+///
+/// Displayed as upper case hex digits, most significant byte first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Digest {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl Digest {
+    fn new(bytes: &[u8]) -> Self {
+        let mut digest = Digest {
+            bytes: [0; 32],
+            len: bytes.len(),
+        };
+        digest.bytes[..bytes.len()].copy_from_slice(bytes);
+        digest
+    }
+
+    /// Returns the value as bytes, most significant byte first.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_bytes()
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02X}"))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum State {
+    Crc32(u32),
+    Crc16(u16),
+    Sum32(u32),
+    #[cfg(feature = "sha2")]
+    Sha256(sha2::Sha256),
+}
+
+/// Incremental checksum computation.
+///
+/// ```ignore
+/// let mut checksum = crate::Checksum::new(crate::ChecksumAlgorithm::Crc32);
+/// checksum.update(b"123456789");
+/// assert_eq!(checksum.value(), 0xCBF43926);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    state: State,
+}
+
+impl Checksum {
+    /// Create a new checksum with no data.
+    pub fn new(algorithm: ChecksumAlgorithm) -> Self {
+        Checksum {
+            state: match algorithm {
+                ChecksumAlgorithm::Crc32 => State::Crc32(!0),
+                ChecksumAlgorithm::Crc16 => State::Crc16(!0),
+                ChecksumAlgorithm::Sum32 => State::Sum32(0),
+                #[cfg(feature = "sha2")]
+                ChecksumAlgorithm::Sha256 => State::Sha256(Default::default()),
+            },
+        }
+    }
+
+    /// Add `data` to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            State::Crc32(crc) => {
+                for byte in data {
+                    *crc = CRC32_TABLE[usize::from(*crc as u8 ^ byte)] ^ (*crc >> 8);
+                }
+            }
+            State::Crc16(crc) => {
+                for byte in data {
+                    *crc ^= u16::from(*byte) << 8;
+                    for _ in 0..8 {
+                        *crc = if *crc & 0x8000 != 0 {
+                            (*crc << 1) ^ 0x1021
+                        } else {
+                            *crc << 1
+                        };
+                    }
+                }
+            }
+            State::Sum32(sum) => {
+                for byte in data {
+                    *sum = sum.wrapping_add(u32::from(*byte));
+                }
+            }
+            #[cfg(feature = "sha2")]
+            State::Sha256(hasher) => sha2::Digest::update(hasher, data),
+        }
+    }
+
+    /// Returns checksum of the data added so far,
+    /// the leading 32 bits for digests wider than that.
+    pub fn value(&self) -> u32 {
+        let digest = self.digest();
+        digest
+            .as_bytes()
+            .iter()
+            .take(4)
+            .fold(0, |value, byte| (value << 8) | u32::from(*byte))
+    }
+
+    /// Returns checksum or digest of the data added so far.
+    pub fn digest(&self) -> Digest {
+        match &self.state {
+            State::Crc32(crc) => Digest::new(&(!crc).to_be_bytes()),
+            State::Crc16(crc) => Digest::new(&crc.to_be_bytes()),
+            State::Sum32(sum) => Digest::new(&sum.to_be_bytes()),
+            #[cfg(feature = "sha2")]
+            State::Sha256(hasher) => Digest::new(&sha2::Digest::finalize(hasher.clone())),
+        }
+    }
+}
+
+/// Verify the `// checksum: XXXXXXXX` footer written by [`crate::Writer`]
+/// against the data in `text`.
+///
+/// Returns `Ok(false)` on mismatch and [`ReaderError::InvalidSyntax`] if there is no footer.
+pub fn verify_checksum(text: &str, algorithm: ChecksumAlgorithm) -> Result<bool, ReaderError> {
+    verify_checksum_with_options(text, algorithm, Default::default())
+}
+
+/// Same as [`verify_checksum`] for `text` read with `options`, e.g. with
+/// [`ReaderOptions::token_bytes`] matching `word_bytes` of the writer.
+///
+/// Data is hashed byte by byte in address order like the writer does.
+/// [`ReaderOptions::transform`] is not applied, the checksum covers the bytes as written.
+pub fn verify_checksum_with_options(
+    text: &str,
+    algorithm: ChecksumAlgorithm,
+    options: ReaderOptions,
+) -> Result<bool, ReaderError> {
+    let footer = text
+        .rfind(FOOTER_PREFIX)
+        .ok_or(ReaderError::InvalidSyntax)?;
+    let expected = text[footer + FOOTER_PREFIX.len()..].trim();
+    if !expected.is_ascii() || !expected.len().is_multiple_of(2) {
+        return Err(ReaderError::BadNumberConversion);
+    }
+
+    let options = ReaderOptions {
+        transform: ByteTransform::None,
+        ..options
+    };
+    let mut checksum = Checksum::new(algorithm);
+    for byte in Reader::new_with_options(&text[..footer], options).bytes() {
+        let (_, value) = byte?;
+        checksum.update(&[value]);
+    }
+
+    let digest = checksum.digest();
+    if expected.len() != 2 * digest.as_bytes().len() {
+        return Ok(false);
+    }
+    for (i, byte) in digest.as_bytes().iter().enumerate() {
+        let expected = u8::from_str_radix(&expected[2 * i..2 * i + 2], 16)
+            .map_err(|_| ReaderError::BadNumberConversion)?;
+        if expected != *byte {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_values() {
+        let mut checksum = Checksum::new(ChecksumAlgorithm::Crc32);
+        checksum.update(b"12345");
+        checksum.update(b"6789");
+        assert_eq!(checksum.value(), 0xCBF43926);
+
+        let mut checksum = Checksum::new(ChecksumAlgorithm::Crc16);
+        checksum.update(b"123456789");
+        assert_eq!(checksum.value(), 0x29B1);
+        assert_eq!(checksum.digest().as_bytes(), [0x29, 0xB1]);
+
+        let mut checksum = Checksum::new(ChecksumAlgorithm::Sum32);
+        checksum.update(b"123456789");
+        assert_eq!(checksum.value(), 0x1DD);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sha2", feature = "std"))]
+    fn test_sha256() {
+        use std::string::ToString;
+
+        let mut checksum = Checksum::new(ChecksumAlgorithm::Sha256);
+        checksum.update(b"abc");
+        assert_eq!(
+            checksum.digest().to_string(),
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+        );
+        assert_eq!(checksum.value(), 0xBA7816BF);
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let text = "@00000010\n31 32 33 34 35 36 37 38 39\n// checksum: CBF43926\n";
+        assert_eq!(verify_checksum(text, ChecksumAlgorithm::Crc32), Ok(true));
+        assert_eq!(verify_checksum(text, ChecksumAlgorithm::Sum32), Ok(false));
+        assert_eq!(
+            verify_checksum("@00000010\n31\n", ChecksumAlgorithm::Crc32),
+            Err(ReaderError::InvalidSyntax)
+        );
+    }
+}
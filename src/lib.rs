@@ -34,7 +34,8 @@
 //! 09 A0 F3 22 20 34 63 84 02 00 6F 00 E0 57 81 40
 //! 01 41 81 41 01 42 81 42 01 43 81 43 01 44 81 44"#;
 //!
-//! let reader = crate::Reader::new_with_options(TEXT_STR, crate::ReaderOptions { group: true });
+//! let options = crate::ReaderOptions { group: true, ..Default::default() };
+//! let reader = crate::Reader::new_with_options(TEXT_STR, options);
 //! for data in reader {
 //!     std::println!("{}", data.unwrap());
 //! }
@@ -53,14 +54,84 @@
 #[cfg(feature = "std")]
 extern crate std;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod any_reader;
+mod checksum;
+mod memory_image;
+#[cfg(feature = "alloc")]
+mod memory_map;
+mod push_parser;
+#[cfg(feature = "std")]
+mod stream;
+mod tokenizer;
+mod writer;
+
+pub use any_reader::{AnyReader, Format, detect_format};
+pub use checksum::{
+    Checksum, ChecksumAlgorithm, Digest, verify_checksum, verify_checksum_with_options,
+};
+pub use memory_image::{ImageError, MemoryImage, load_into};
+pub use push_parser::{MAX_TOKEN_LEN, PushParser};
+pub use tokenizer::Position;
+pub use writer::{
+    LaneWriter, LineEnding, TranscodeError, Writer, WriterError, WriterOptions,
+    WriterOptionsBuilder, transcode,
+};
+
+#[cfg(feature = "alloc")]
+pub use memory_map::{
+    ConflictPolicy, Difference, MapError, MemoryMap, MergeError, Overlay, Region, Stats,
+};
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use memory_map::FrozenMap;
+
+#[cfg(feature = "async")]
+pub use stream::AsyncStreamReader;
+#[cfg(feature = "std")]
+pub use stream::{StreamError, StreamReader};
+#[cfg(feature = "std")]
+pub use writer::{IoWriter, Shard, ShardWriter, normalize};
+
 use core::error::Error;
 use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::{Range, RangeInclusive};
 use core::str;
 
-type Addr = u64;
+use tokenizer::{Token, Tokenizer};
+
+/// Address type, `u64` unless the `addr-u32` or `addr-u128` feature is enabled,
+/// `addr-u128` wins if both are. Address differences and lengths use it too.
+#[cfg(not(any(feature = "addr-u32", feature = "addr-u128")))]
+pub type Addr = u64;
+/// Signed address offset of the same width as [`Addr`].
+#[cfg(not(any(feature = "addr-u32", feature = "addr-u128")))]
+pub type AddrOffset = i64;
+
+/// Address type, `u64` unless the `addr-u32` or `addr-u128` feature is enabled,
+/// `addr-u128` wins if both are. Address differences and lengths use it too.
+#[cfg(all(feature = "addr-u32", not(feature = "addr-u128")))]
+pub type Addr = u32;
+/// Signed address offset of the same width as [`Addr`].
+#[cfg(all(feature = "addr-u32", not(feature = "addr-u128")))]
+pub type AddrOffset = i32;
+
+/// Address type, `u64` unless the `addr-u32` or `addr-u128` feature is enabled,
+/// `addr-u128` wins if both are. Address differences and lengths use it too.
+#[cfg(feature = "addr-u128")]
+pub type Addr = u128;
+/// Signed address offset of the same width as [`Addr`].
+#[cfg(feature = "addr-u128")]
+pub type AddrOffset = i128;
 
 /// Bytes in a line are grouped into N groups of M bytes each.
+///
+/// See [`DataWord`] for the same value as bits and width.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataType {
     U8(u8),
     U16(u16),
@@ -70,56 +141,384 @@ pub enum DataType {
     U48(u64),
     U56(u64),
     U64(u64),
+    U72(u128),
+    U80(u128),
+    U88(u128),
+    U96(u128),
+    U104(u128),
+    U112(u128),
+    U120(u128),
+    U128(u128),
+}
+
+impl DataType {
+    /// Returns the value, zero-extended.
+    pub fn as_u128(self) -> u128 {
+        DataWord::from(self).value()
+    }
+
+    /// Returns the value, `None` if the type is wider than 64 bits.
+    pub fn as_u64(self) -> Option<u64> {
+        (self.byte_len() <= 8).then(|| self.as_u128() as u64)
+    }
+
+    /// Returns width in bytes, 1 for [`DataType::U8`] up to 16 for [`DataType::U128`].
+    pub fn byte_len(self) -> usize {
+        DataWord::from(self).bytes()
+    }
+
+    /// Returns width in bits.
+    pub fn width_bits(self) -> u32 {
+        8 * self.byte_len() as u32
+    }
+
+    /// Returns [`DataType::byte_len`] bytes of the value in `endian` order.
+    pub fn to_bytes(self, endian: Endian) -> DataBytes {
+        let (value, len) = split_data(self);
+        let mut bytes = [0; 16];
+        match endian {
+            Endian::Little => bytes[..len].copy_from_slice(&value.to_le_bytes()[..len]),
+            Endian::Big => bytes[..len].copy_from_slice(&value.to_be_bytes()[16 - len..]),
+        }
+        DataBytes { bytes, len }
+    }
+
+    /// Returns bytes of the value, least significant first.
+    pub fn to_le_bytes(self) -> DataBytes {
+        self.to_bytes(Endian::Little)
+    }
+
+    /// Returns bytes of the value, most significant first.
+    pub fn to_be_bytes(self) -> DataBytes {
+        self.to_bytes(Endian::Big)
+    }
+}
+
+/// Grouped value as its bits and width in bytes, the same data as a [`DataType`]
+/// without a match over every width. Convert with [`From`] both ways.
+///
+/// ```ignore
+/// let word = crate::DataWord::from(crate::DataType::U24(0x0A0B0C));
+/// assert_eq!((word.value(), word.bytes()), (0x0A0B0C, 3));
+/// ```
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DataWord {
+    value: u128,
+    bytes: u8,
+}
+
+impl DataWord {
+    /// Create a word of the low `bytes` bytes of `value`, `bytes` is clamped to 1..=16.
+    pub fn new(value: u128, bytes: usize) -> Self {
+        let bytes = bytes.clamp(1, 16);
+        let value = match bytes {
+            16 => value,
+            _ => value & ((1 << (8 * bytes)) - 1),
+        };
+        DataWord {
+            value,
+            bytes: bytes as u8,
+        }
+    }
+
+    /// Returns the value.
+    pub fn value(self) -> u128 {
+        self.value
+    }
+
+    /// Returns width in bytes, 1..=16.
+    pub fn bytes(self) -> usize {
+        usize::from(self.bytes)
+    }
+}
+
+impl From<DataType> for DataWord {
+    fn from(value: DataType) -> Self {
+        let (value, bytes) = match value {
+            DataType::U8(value) => (u128::from(value), 1),
+            DataType::U16(value) => (u128::from(value), 2),
+            DataType::U24(value) => (u128::from(value), 3),
+            DataType::U32(value) => (u128::from(value), 4),
+            DataType::U40(value) => (u128::from(value), 5),
+            DataType::U48(value) => (u128::from(value), 6),
+            DataType::U56(value) => (u128::from(value), 7),
+            DataType::U64(value) => (u128::from(value), 8),
+            DataType::U72(value) => (value, 9),
+            DataType::U80(value) => (value, 10),
+            DataType::U88(value) => (value, 11),
+            DataType::U96(value) => (value, 12),
+            DataType::U104(value) => (value, 13),
+            DataType::U112(value) => (value, 14),
+            DataType::U120(value) => (value, 15),
+            DataType::U128(value) => (value, 16),
+        };
+        DataWord { value, bytes }
+    }
+}
+
+impl From<DataWord> for DataType {
+    fn from(word: DataWord) -> Self {
+        match word.bytes {
+            1 => DataType::U8(word.value as u8),
+            2 => DataType::U16(word.value as u16),
+            3 => DataType::U24(word.value as u32),
+            4 => DataType::U32(word.value as u32),
+            5 => DataType::U40(word.value as u64),
+            6 => DataType::U48(word.value as u64),
+            7 => DataType::U56(word.value as u64),
+            8 => DataType::U64(word.value as u64),
+            9 => DataType::U72(word.value),
+            10 => DataType::U80(word.value),
+            11 => DataType::U88(word.value),
+            12 => DataType::U96(word.value),
+            13 => DataType::U104(word.value),
+            14 => DataType::U112(word.value),
+            15 => DataType::U120(word.value),
+            _ => DataType::U128(word.value),
+        }
+    }
+}
+
+/// Values are zero-padded to their width unless the format gives a width,
+/// e.g. `{:x}` of `DataType::U16(0xAB)` is `00ab`.
+macro_rules! impl_data_type_fmt {
+    ($($trait:ident, $digits:expr, $spec:literal, $alternate:literal);*) => {$(
+        impl fmt::$trait for DataType {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let word = DataWord::from(*self);
+                let width = word.bytes() * $digits;
+                match (f.width(), f.alternate()) {
+                    (Some(_), _) => fmt::$trait::fmt(&word.value(), f),
+                    (None, false) => write!(f, $spec, word.value(), width = width),
+                    (None, true) => write!(f, $alternate, word.value(), width = width + 2),
+                }
+            }
+        }
+    )*};
+}
+
+impl_data_type_fmt!(
+    LowerHex, 2, "{:0width$x}", "{:#0width$x}";
+    UpperHex, 2, "{:0width$X}", "{:#0width$X}";
+    Binary, 8, "{:0width$b}", "{:#0width$b}"
+);
+
+/// Error converting a [`DataType`] to a narrower integer type.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DataWidthError {
+    /// Width of the value in bits.
+    pub bits: u32,
+}
+
+impl fmt::Display for DataWidthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-bit data does not fit", self.bits)
+    }
+}
+
+impl Error for DataWidthError {}
+
+macro_rules! impl_try_from_data_type {
+    ($($t:ty),*) => {$(
+        impl TryFrom<DataType> for $t {
+            type Error = DataWidthError;
+
+            /// Fails if `value` is wider than the integer, regardless of the value.
+            fn try_from(value: DataType) -> Result<Self, Self::Error> {
+                if value.byte_len() > core::mem::size_of::<$t>() {
+                    return Err(DataWidthError {
+                        bits: value.width_bits(),
+                    });
+                }
+                Ok(value.as_u128() as $t)
+            }
+        }
+    )*};
+}
+
+impl_try_from_data_type!(u8, u16, u32, u64);
+
+impl From<DataType> for u128 {
+    fn from(value: DataType) -> Self {
+        value.as_u128()
+    }
+}
+
+/// Bytes of a [`DataType`] value, derefs to a slice of [`DataType::byte_len`] bytes.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DataBytes {
+    bytes: [u8; 16],
+    len: usize,
+}
+
+impl core::ops::Deref for DataBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for DataBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
 }
 
 /// Syntax token type.
+///
+/// With the `serde` feature, deserialized text fields borrow from the input,
+/// which fails for escaped strings.
 #[derive(Debug, PartialEq)]
-pub enum Record {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Record<'a> {
     Data {
         addr: Addr,
         value: DataType,
     },
     EndOfFile,
-    Comment,
+    /// Text after `//`, borrowed from the input.
+    Comment(&'a str),
 
     /// Example: @81000000
     NewAddress(Addr),
+
+    /// Byte with `x` or `z` digits, e.g. `1x` from `$writememh`, read with
+    /// [`ReaderOptions::xz_digits`]. Bits set in `mask` are unknown and zero in `value`.
+    Unknown {
+        addr: Addr,
+        value: u8,
+        mask: u8,
+    },
+
+    /// Data tokens of one input line starting at `addr`, read with
+    /// [`ReaderOptions::data_lines`]. `hex` is the raw text, e.g. `09 A0 F3`.
+    DataLine {
+        addr: Addr,
+        hex: &'a str,
+    },
+
+    /// Symbol from an objdump-style comment, e.g. `// 81000000 <_start>:`,
+    /// read with [`ReaderOptions::symbols`].
+    Symbol {
+        addr: Addr,
+        name: &'a str,
+    },
 }
 
-impl fmt::Display for Record {
+impl fmt::Display for Record<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Record::EndOfFile => write!(f, "EOF"),
-            Record::Comment => write!(f, "comment"),
+            Record::Comment(text) => write!(f, "comment: {text}"),
             Record::NewAddress(addr) => write!(f, "new address: {addr:#010X}"),
             Record::Data { addr, value } => {
-                write!(
-                    f,
-                    "{:#010X}: {:02X}",
-                    addr,
-                    match value {
-                        DataType::U8(value) => u64::from(*value),
-                        DataType::U16(value) => u64::from(*value),
-                        DataType::U24(value) => u64::from(*value),
-                        DataType::U32(value) => u64::from(*value),
-                        DataType::U40(value) => *value,
-                        DataType::U48(value) => *value,
-                        DataType::U56(value) => *value,
-                        DataType::U64(value) => *value,
-                    }
-                )
+                write!(f, "{:#010X}: {:02X}", addr, split_data(*value).0)
+            }
+            Record::Unknown { addr, value, mask } => {
+                write!(f, "{addr:#010X}: ")?;
+                write_xz(f, *value, *mask, HexCase::Upper)
             }
+            Record::DataLine { addr, hex } => write!(f, "{addr:#010X}: {hex}"),
+            Record::Symbol { addr, name } => write!(f, "{addr:#010X}: <{name}>"),
         }
     }
 }
 
+/// Write `record` like [`fmt::Display`] with addresses in `hex_case`
+/// and data in hex of the same case, or in binary.
+fn write_record(
+    f: &mut fmt::Formatter,
+    record: &Record,
+    hex_case: HexCase,
+    binary: bool,
+) -> fmt::Result {
+    let write_addr = |f: &mut fmt::Formatter, addr: Addr| {
+        write!(f, "0x")?;
+        hex_case.write_hex(f, addr, 8)
+    };
+    match record {
+        Record::Data { addr, value } => {
+            write_addr(f, *addr)?;
+            match (binary, hex_case) {
+                (true, _) => write!(f, ": {value:b}"),
+                (false, HexCase::Upper) => write!(f, ": {value:X}"),
+                (false, HexCase::Lower) => write!(f, ": {value:x}"),
+            }
+        }
+        Record::Unknown { addr, value, mask } => {
+            write_addr(f, *addr)?;
+            write!(f, ": ")?;
+            if !binary {
+                return write_xz(f, *value, *mask, hex_case);
+            }
+            for bit in (0..8).rev() {
+                match ((mask >> bit) & 1, (value >> bit) & 1) {
+                    (1, _) => write!(f, "x")?,
+                    (_, bit) => write!(f, "{bit}")?,
+                }
+            }
+            Ok(())
+        }
+        Record::NewAddress(addr) => {
+            write!(f, "new address: ")?;
+            write_addr(f, *addr)
+        }
+        Record::DataLine { addr, hex } => {
+            write_addr(f, *addr)?;
+            write!(f, ": {hex}")
+        }
+        Record::Symbol { addr, name } => {
+            write_addr(f, *addr)?;
+            write!(f, ": <{name}>")
+        }
+        Record::EndOfFile | Record::Comment(_) => write!(f, "{record}"),
+    }
+}
+
+/// Same as [`fmt::Display`] with lowercase hex digits and data zero-padded to its width.
+impl fmt::LowerHex for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_record(f, self, HexCase::Lower, false)
+    }
+}
+
+/// Same as [`fmt::Display`] with data zero-padded to its width.
+impl fmt::UpperHex for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_record(f, self, HexCase::Upper, false)
+    }
+}
+
+/// Same as [`fmt::Display`] with data in binary, unknown bits as `x`.
+impl fmt::Binary for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_record(f, self, HexCase::Upper, true)
+    }
+}
+
 /// Custom simple error type.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReaderError {
     /// Failed to parse tokens.
     InvalidSyntax,
     /// Can't convert string to number.
     BadNumberConversion,
+    /// Data token does not have exactly two hex digits per byte in strict mode.
+    BadTokenWidth,
+    /// Token outside of a comment has non-ASCII bytes.
+    NonAscii,
+    /// Block comment `/*` has no closing `*/`.
+    UnterminatedComment,
+    /// Data is outside of [`ReaderOptions::window`].
+    OutsideWindow,
+    /// Data exceeds [`ReaderOptions::max_addr`] or [`ReaderOptions::max_bytes`].
+    OutOfRange,
+    /// Address moves backwards with [`ReaderOptions::ascending`].
+    DescendingAddress,
+    /// Intel HEX or S-record line checksum does not match, see [`AnyReader`].
+    BadChecksum,
 }
 
 impl fmt::Display for ReaderError {
@@ -127,55 +526,403 @@ impl fmt::Display for ReaderError {
         match self {
             ReaderError::InvalidSyntax => write!(f, "invalid format"),
             ReaderError::BadNumberConversion => write!(f, "cant convert string to number"),
+            ReaderError::BadTokenWidth => write!(f, "data token has wrong number of hex digits"),
+            ReaderError::NonAscii => write!(f, "non-ASCII character"),
+            ReaderError::UnterminatedComment => write!(f, "unterminated block comment"),
+            ReaderError::OutsideWindow => write!(f, "data outside of address window"),
+            ReaderError::OutOfRange => write!(f, "data exceeds memory size"),
+            ReaderError::DescendingAddress => write!(f, "address moves backwards"),
+            ReaderError::BadChecksum => write!(f, "line checksum mismatch"),
         }
     }
 }
 
 impl Error for ReaderError {}
 
-impl Record {
+/// [`ReaderError`] with the location and the text of the offending token.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseError<'a> {
+    /// What went wrong.
+    pub kind: ReaderError,
+    /// 1-based line of the token.
+    pub line: usize,
+    /// 1-based column of the token in bytes.
+    pub column: usize,
+    /// Offending token.
+    pub token: &'a str,
+}
+
+impl fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} at `{}`",
+            self.line, self.column, self.kind, self.token
+        )
+    }
+}
+
+impl Error for ParseError<'_> {}
+
+impl From<ParseError<'_>> for ReaderError {
+    fn from(error: ParseError<'_>) -> Self {
+        error.kind
+    }
+}
+
+/// [`ParseError`] that does not borrow the input, e.g. to return it after the input
+/// is dropped. Keeps the first [`OwnedParseError::TOKEN_LEN`] bytes of the token.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OwnedParseError {
+    /// What went wrong.
+    pub kind: ReaderError,
+    /// 1-based line of the token.
+    pub line: usize,
+    /// 1-based column of the token in bytes.
+    pub column: usize,
+    token: [u8; OwnedParseError::TOKEN_LEN],
+    token_len: usize,
+}
+
+impl OwnedParseError {
+    /// Longest token text kept.
+    pub const TOKEN_LEN: usize = 32;
+
+    /// Returns the offending token, cut to [`OwnedParseError::TOKEN_LEN`] bytes.
+    pub fn token(&self) -> &str {
+        str::from_utf8(&self.token[..self.token_len]).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for OwnedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} at `{}`",
+            self.line,
+            self.column,
+            self.kind,
+            self.token()
+        )
+    }
+}
+
+impl Error for OwnedParseError {}
+
+impl From<ParseError<'_>> for OwnedParseError {
+    fn from(error: ParseError<'_>) -> Self {
+        let mut token_len = error.token.len().min(OwnedParseError::TOKEN_LEN);
+        while !error.token.is_char_boundary(token_len) {
+            token_len -= 1;
+        }
+        let mut token = [0; OwnedParseError::TOKEN_LEN];
+        token[..token_len].copy_from_slice(&error.token.as_bytes()[..token_len]);
+        OwnedParseError {
+            kind: error.kind,
+            line: error.line,
+            column: error.column,
+            token,
+            token_len,
+        }
+    }
+}
+
+impl From<OwnedParseError> for ReaderError {
+    fn from(error: OwnedParseError) -> Self {
+        error.kind
+    }
+}
+
+impl<'a> Record<'a> {
+    /// Returns a [`fmt::Display`] adapter emitting the record as it is written in a file,
+    /// e.g. `@81000000` or `09`, unlike the human-oriented `Display` of the record itself.
+    ///
+    /// ```ignore
+    /// let record = Record::NewAddress(0x8100abcd);
+    /// assert_eq!(record.to_verilog_syntax().to_string(), "@8100ABCD");
+    /// ```
+    pub fn to_verilog_syntax(&self) -> VerilogSyntax<'_> {
+        VerilogSyntax {
+            record: self,
+            hex_case: HexCase::default(),
+        }
+    }
+
     /// Constructs a new [`Record`] by parsing `string`.
-    pub fn from_string(string: &str, current_addr: Addr) -> Result<Self, ReaderError> {
-        if string.is_empty() {
-            return Err(ReaderError::InvalidSyntax);
+    pub fn from_string(string: &'a str, current_addr: Addr) -> Result<Self, ReaderError> {
+        if let Some(text) = string.strip_prefix("//") {
+            return Ok(Record::Comment(text));
+        }
+        parse_value(string, current_addr)
+    }
+}
+
+/// Parse address or data token `string`, the part of [`Record::from_string`]
+/// that does not borrow from the input.
+fn parse_value(string: &str, current_addr: Addr) -> Result<Record<'static>, ReaderError> {
+    if string.is_empty() {
+        return Err(ReaderError::InvalidSyntax);
+    }
+
+    if let Some(stripped_string) = string.strip_prefix('@') {
+        if let Ok(value) = Addr::from_str_radix(stripped_string, 16) {
+            return Ok(Record::NewAddress(value));
+        } else {
+            return Err(ReaderError::BadNumberConversion);
         }
+    }
+
+    if let Ok(value) = u8::from_str_radix(string, 16) {
+        Ok(Record::Data {
+            addr: current_addr,
+            value: DataType::U8(value),
+        })
+    } else {
+        Err(ReaderError::BadNumberConversion)
+    }
+}
+
+/// Byte order of multi-byte words.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum Endian {
+    /// Lowest address holds the least significant byte.
+    #[default]
+    Little,
+    /// Lowest address holds the most significant byte.
+    Big,
+}
 
-        if string.starts_with("//") {
-            return Ok(Record::Comment);
+/// Case of hex digits in generated text.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum HexCase {
+    /// `0A1B`, same as `objcopy -O verilog`.
+    #[default]
+    Upper,
+    /// `0a1b`.
+    Lower,
+}
+
+impl HexCase {
+    /// Write `value` as `width` hex digits.
+    pub(crate) fn write_hex<W: fmt::Write>(
+        self,
+        f: &mut W,
+        value: impl fmt::UpperHex + fmt::LowerHex,
+        width: usize,
+    ) -> fmt::Result {
+        match self {
+            HexCase::Upper => write!(f, "{value:0width$X}"),
+            HexCase::Lower => write!(f, "{value:0width$x}"),
         }
+    }
+}
+
+/// Displays a [`Record`] in Verilog hex file syntax, see [`Record::to_verilog_syntax`].
+pub struct VerilogSyntax<'r> {
+    record: &'r Record<'r>,
+    hex_case: HexCase,
+}
+
+impl VerilogSyntax<'_> {
+    /// Select case of hex digits, upper case by default.
+    pub fn hex_case(mut self, hex_case: HexCase) -> Self {
+        self.hex_case = hex_case;
+        self
+    }
+}
 
-        if let Some(stripped_string) = string.strip_prefix('@') {
-            if let Ok(value) = u64::from_str_radix(stripped_string, 16) {
-                return Ok(Record::NewAddress(value));
-            } else {
-                return Err(ReaderError::BadNumberConversion);
+impl fmt::Display for VerilogSyntax<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.record {
+            Record::EndOfFile => Ok(()),
+            Record::Comment(text) => write!(f, "//{text}"),
+            Record::NewAddress(addr) => {
+                write!(f, "@")?;
+                self.hex_case.write_hex(f, *addr, 8)
+            }
+            Record::Data { addr: _, value } => {
+                let (value, len) = split_data(*value);
+                self.hex_case.write_hex(f, value, 2 * len)
+            }
+            Record::Unknown {
+                addr: _,
+                value,
+                mask,
+            } => write_xz(f, *value, *mask, self.hex_case),
+            Record::DataLine { addr: _, hex } => write!(f, "{hex}"),
+            Record::Symbol { addr, name } => {
+                write!(f, "// ")?;
+                self.hex_case.write_hex(f, *addr, 8)?;
+                write!(f, " <{name}>:")
             }
         }
+    }
+}
 
-        if let Ok(value) = u8::from_str_radix(string, 16) {
-            Ok(Record::Data {
-                addr: current_addr,
-                value: DataType::U8(value),
-            })
+/// Write two digits of `value` with `x` for unknown nibbles in `mask`.
+fn write_xz(f: &mut fmt::Formatter, value: u8, mask: u8, hex_case: HexCase) -> fmt::Result {
+    for shift in [4, 0] {
+        if (mask >> shift) & 0xF != 0 {
+            let x = match hex_case {
+                HexCase::Upper => "X",
+                HexCase::Lower => "x",
+            };
+            write!(f, "{x}")?;
         } else {
-            Err(ReaderError::BadNumberConversion)
+            hex_case.write_hex(f, (value >> shift) & 0xF, 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Per-byte transform, e.g. for ROM generators expecting bit-reversed bytes.
+///
+/// Every transform is its own inverse, so the same value undoes it on the other side.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum ByteTransform {
+    /// Bytes are kept as is.
+    #[default]
+    None,
+    /// Bit 0 becomes bit 7 and so on.
+    BitReverse,
+    /// High and low nibbles are exchanged.
+    NibbleSwap,
+}
+
+impl ByteTransform {
+    /// Returns transformed `value`.
+    pub fn apply(self, value: u8) -> u8 {
+        match self {
+            ByteTransform::None => value,
+            ByteTransform::BitReverse => value.reverse_bits(),
+            ByteTransform::NibbleSwap => value.rotate_left(4),
+        }
+    }
+}
+
+/// Unit of addresses in `@` records.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum AddrUnit {
+    /// Addresses count bytes, as written by `objcopy`.
+    #[default]
+    Bytes,
+    /// Addresses count words of the given width in bytes, as read by `$readmemh`
+    /// into a wide memory.
+    Words(usize),
+}
+
+impl AddrUnit {
+    /// Returns byte address of `addr` counted in this unit, `None` on overflow.
+    pub fn to_byte_addr(self, addr: Addr) -> Option<Addr> {
+        match self {
+            AddrUnit::Bytes => Some(addr),
+            AddrUnit::Words(width) => addr.checked_mul(width as Addr),
         }
     }
 }
 
 /// Configuration options for the reader.
-#[derive(Default)]
-pub struct ReaderOptions {
-    /// Group bytes into 2..8 bytes.
+#[derive(Clone)]
+pub struct ReaderOptions<'a> {
+    /// Group bytes into 2..`group_bytes` bytes.
     pub group: bool,
+    /// Maximum number of bytes in a group, 1..=16, default is 8.
+    pub group_bytes: usize,
+    /// End groups at addresses that are multiples of `group_bytes`,
+    /// so an unaligned start gives a short leading group.
+    pub align_groups: bool,
+    /// End groups at the end of input lines.
+    pub group_within_line: bool,
+    /// Require exactly two hex digits per data byte, e.g. reject `9` and `009`,
+    /// or eight binary digits with `binary`.
+    pub strict: bool,
+    /// Keep reading after an error, the bad token is reported and skipped.
+    pub recover: bool,
+    /// Unit of addresses in `@` records, records always carry byte addresses.
+    pub addr_unit: AddrUnit,
+    /// Address aliases as `(range, target)`, e.g. `&[(0..0x1_0000, 0x8100_0000)]` to load
+    /// an image linked for a boot window at its physical address. Applied to `@` byte
+    /// addresses, the first matching range wins and data continues from the new address.
+    pub remap: &'a [(Range<Addr>, Addr)],
+    /// Ignore `_` separators in data and address tokens, e.g. `@8100_0000`.
+    pub underscores: bool,
+    /// Read data tokens with `x` or `z` digits as [`Record::Unknown`].
+    pub xz_digits: bool,
+    /// Bytes per data token, 1..=16, default is 1, e.g. 4 for 8-digit tokens written by
+    /// `objcopy -O verilog --verilog-data-width=4`. The token value is a little-endian
+    /// word, so its lowest byte is at the lowest address.
+    /// Grouping applies to single-byte tokens only.
+    pub token_bytes: usize,
+    /// Read data tokens as binary digits, as `$readmemb` does, 8 digits per byte.
+    /// Addresses are still hex. Does not combine with `xz_digits`.
+    pub binary: bool,
+    /// Address window of data, like the `start` and `finish` arguments of `$readmemh`.
+    /// Data outside of it is skipped, or reported with `window_error`.
+    pub window: Option<RangeInclusive<Addr>>,
+    /// Report data outside of `window` as [`ReaderError::OutsideWindow`].
+    pub window_error: bool,
+    /// Highest address of data, e.g. the last byte of the target memory.
+    /// Data above it is reported as [`ReaderError::OutOfRange`].
+    pub max_addr: Option<Addr>,
+    /// Maximum number of data bytes read, more data is reported as
+    /// [`ReaderError::OutOfRange`].
+    pub max_bytes: Option<usize>,
+    /// Report an `@` record below the current address as
+    /// [`ReaderError::DescendingAddress`], so data can never cover an address twice.
+    pub ascending: bool,
+    /// Token separators in addition to ASCII whitespace, e.g. `&[',', '\u{A0}']`
+    /// for comma separated bytes or non-breaking spaces. Comments are not split.
+    pub delimiters: &'a [char],
+    /// Line comment markers in addition to `//`, e.g. `&["#", ";"]` for vendor dialects.
+    /// Like `//`, they comment out the rest of the line.
+    pub comment_prefixes: &'a [&'a str],
+    /// Return [`Record::EndOfFile`] at the end of input, not after an error.
+    pub end_of_file: bool,
+    /// Read comments like `// 81000000 <_start>:` as [`Record::Symbol`].
+    pub symbols: bool,
+    /// Return data tokens of every line as one [`Record::DataLine`] without decoding them,
+    /// e.g. for bulk processing of huge files. Other data options do not apply.
+    /// [`Reader::bytes`] and the loaders of data still decode the lines.
+    /// Not supported by [`PushParser`].
+    pub data_lines: bool,
+    /// Set `token_bytes` from the digit count of the first data token and turn `strict`
+    /// on, so later tokens of another width are reported as [`ReaderError::BadTokenWidth`].
+    pub detect_token_bytes: bool,
+    /// Transform applied to every data byte before grouping.
+    pub transform: ByteTransform,
 }
 
-/* Can be derived so far
-impl Default for ReaderOptions {
+impl Default for ReaderOptions<'_> {
     fn default() -> Self {
-        ReaderOptions { group: false }
+        ReaderOptions {
+            group: false,
+            group_bytes: 8,
+            align_groups: false,
+            group_within_line: false,
+            strict: false,
+            recover: false,
+            addr_unit: AddrUnit::Bytes,
+            remap: &[],
+            underscores: false,
+            xz_digits: false,
+            token_bytes: 1,
+            binary: false,
+            window: None,
+            window_error: false,
+            max_addr: None,
+            max_bytes: None,
+            ascending: false,
+            delimiters: &[],
+            comment_prefixes: &[],
+            end_of_file: false,
+            symbols: false,
+            data_lines: false,
+            detect_token_bytes: false,
+            transform: ByteTransform::None,
+        }
     }
-}*/
+}
 
 /// A reader for Verilog hex files.
 ///
@@ -195,131 +942,898 @@ impl Default for ReaderOptions {
 ///     }))
 /// );
 /// ```
+#[derive(Clone)]
 pub struct Reader<'a> {
     /// Iterator over tokens.
-    token_iterator: core::iter::Peekable<str::SplitAsciiWhitespace<'a>>,
+    token_iterator: Tokenizer<'a>,
     /// Reading may complete earlier.
     finished: bool,
     /// Configuration options.
-    #[allow(dead_code)]
-    options: ReaderOptions,
+    options: ReaderOptions<'a>,
     /// Current address.
     current_addr: Addr,
+    /// Number of data bytes read.
+    data_bytes: usize,
 }
 
 impl<'a> Reader<'a> {
     /// Create a new reader with the specified options.
-    pub fn new_with_options(string: &'a str, options: ReaderOptions) -> Self {
+    pub fn new_with_options(string: &'a str, options: ReaderOptions<'a>) -> Self {
+        Reader::from_bytes_with_options(string.as_bytes(), options)
+    }
+
+    /// Create a new reader with default options.
+    pub fn new(string: &'a str) -> Self {
+        Reader::new_with_options(string, Default::default())
+    }
+
+    /// Create a new reader of raw `bytes` with the specified options, e.g. a file in flash,
+    /// without validating it as UTF-8 first.
+    ///
+    /// Non-ASCII bytes outside of comments are reported as [`ReaderError::NonAscii`].
+    pub fn from_bytes_with_options(bytes: &'a [u8], options: ReaderOptions<'a>) -> Self {
         Reader {
-            token_iterator: string.split_ascii_whitespace().peekable(), // whitespaces + newlines
+            token_iterator: Tokenizer::new(bytes)
+                .with_delimiters(options.delimiters)
+                .with_comment_prefixes(options.comment_prefixes),
             finished: false,
             options,
             current_addr: 0,
+            data_bytes: 0,
         }
     }
 
-    /// Create a new reader with default options.
-    pub fn new(string: &'a str) -> Self {
-        Reader::new_with_options(string, Default::default())
+    /// Create a new reader of raw `bytes` with default options.
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        Reader::from_bytes_with_options(bytes, Default::default())
     }
 
-    /// Private helper method for obtaining the next record string.
-    /// Does not respect the 'finished' flag.
-    /// It will return either the next record string to be read, or None if nothing is left to process.
-    fn next_record(&mut self) -> Option<&'a str> {
-        self.token_iterator
-            .by_ref()
-            .find(|&token| !token.is_empty())
+    /// Returns iterator over `(address, byte)` of data records only,
+    /// grouped values and [`Record::DataLine`] are split back into bytes.
+    pub fn bytes(self) -> Bytes<'a> {
+        Bytes {
+            reader: self.data_records(),
+            addr: 0,
+            value: 0,
+            len: 0,
+        }
     }
-}
 
-impl<'a> Iterator for Reader<'a> {
-    type Item = Result<Record, ReaderError>;
+    /// Returns iterator over `(address, word)` of aligned words with data,
+    /// bytes missing in a word read as `fill`.
+    ///
+    /// ```ignore
+    /// for word in crate::Reader::new(TEXT_STR).words::<u32>(crate::Endian::Little, 0) {
+    ///     let (addr, value) = word?;
+    /// }
+    /// ```
+    pub fn words<T: Word>(self, endian: Endian, fill: u8) -> Words<'a, T> {
+        Words {
+            bytes: self.bytes(),
+            endian,
+            fill,
+            peeked: None,
+            word: core::marker::PhantomData,
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Returns iterator over runs of data bytes at contiguous addresses,
+    /// found on the fly without building a map.
+    ///
+    /// ```ignore
+    /// for run in crate::Reader::new(TEXT_STR).runs() {
+    ///     let run = run?;
+    ///     flash.program(run.start, run.len, run);
+    /// }
+    /// ```
+    pub fn runs(self) -> Runs<'a> {
+        Runs {
+            bytes: self.bytes(),
+        }
+    }
+
+    /// Returns iterator over `(address, byte)` of every `stride`-th byte address
+    /// starting at `lane`, with lane-local addresses `addr / stride`, e.g. to
+    /// extract one byte lane of a wide memory.
+    ///
+    /// Panics if `lane` is not less than `stride`.
+    pub fn lane(self, stride: Addr, lane: Addr) -> Lane<'a> {
+        assert!(lane < stride, "lane must be less than stride");
+        Lane {
+            bytes: self.bytes(),
+            stride,
+            lane,
+        }
+    }
+
+    /// Skip data before address `addr` without converting it, e.g. to extract a small
+    /// window of a huge file. Skipped data tokens are not validated.
+    ///
+    /// Stops before the first data token holding `addr` or a higher address, or before an
+    /// address token that fails to parse. With [`ReaderOptions::token_bytes`] above 1 the
+    /// next record may start below `addr`. Returns `false` if the end of input is reached first.
+    pub fn seek_to(&mut self, addr: Addr) -> bool {
         if self.finished {
-            return None;
+            return false;
+        }
+        loop {
+            let mut ahead = self.token_iterator.clone();
+            let Some(token) = ahead.next() else {
+                return false;
+            };
+            if let Some(new_addr) = token.text.strip_prefix('@') {
+                let new_addr = Addr::from_str_radix(new_addr, 16).ok();
+                match new_addr.and_then(|addr| map_addr(addr, &self.options)) {
+                    Some(new_addr) => self.current_addr = new_addr,
+                    None => return true,
+                }
+            } else if token.text.starts_with("/*") {
+                return true;
+            } else if comment_text(token.text, &self.options).is_none() {
+                detect_token_bytes(&mut self.options, token);
+                let token_bytes = self.options.token_bytes.clamp(1, 16) as Addr;
+                let end = self.current_addr.checked_add(token_bytes);
+                if end.is_none_or(|end| end > addr) {
+                    return true;
+                }
+                self.current_addr += token_bytes;
+            }
+            self.token_iterator = ahead;
         }
+    }
 
-        match self.next_record() {
-            None => {
-                self.finished = true;
-                None
+    /// Returns the highest data address from the current position to the end of input,
+    /// e.g. to size a memory, `None` if there is no data.
+    ///
+    /// Only address tokens are converted, like in [`Reader::seek_to`],
+    /// the scan stops at an address token that fails to parse.
+    pub fn last_address(&self) -> Option<Addr> {
+        let mut reader = self.clone();
+        let mut last = None;
+        while reader.seek_to(0) {
+            let token = reader.token_iterator.next()?;
+            if token.text.starts_with(['@', '/']) {
+                break;
             }
+            let token_bytes = reader.options.token_bytes.clamp(1, 16) as Addr;
+            let end = reader.current_addr + (token_bytes - 1);
+            last = last.max(Some(end));
+            reader.current_addr += token_bytes;
+        }
+        last
+    }
 
-            Some(token) => {
-                let mut parse_result = Record::from_string(token, self.current_addr);
+    /// Returns the next record without consuming it.
+    pub fn peek_record(&self) -> Option<Result<Record<'a>, ParseError<'a>>> {
+        self.clone().next()
+    }
 
-                if parse_result.is_err() {
-                    self.finished = true;
-                }
+    /// Returns the current reading state to continue from later with [`Reader::restore`],
+    /// e.g. after a speculative look-ahead.
+    ///
+    /// ```ignore
+    /// let checkpoint = reader.checkpoint();
+    /// let next_addr = reader.by_ref().find_map(|r| match r {
+    ///     Ok(Record::NewAddress(addr)) => Some(addr),
+    ///     _ => None,
+    /// });
+    /// reader.restore(checkpoint);
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint {
+            token_iterator: self.token_iterator.clone(),
+            finished: self.finished,
+            current_addr: self.current_addr,
+            data_bytes: self.data_bytes,
+        }
+    }
 
-                if let Ok(Record::EndOfFile) = parse_result {
-                    self.finished = true;
-                }
+    /// Continue reading from `checkpoint` taken by [`Reader::checkpoint`].
+    pub fn restore(&mut self, checkpoint: Checkpoint<'a>) {
+        self.token_iterator = checkpoint.token_iterator;
+        self.finished = checkpoint.finished;
+        self.current_addr = checkpoint.current_addr;
+        self.data_bytes = checkpoint.data_bytes;
+    }
 
-                if let Ok(Record::NewAddress(new_addr)) = parse_result {
-                    self.current_addr = new_addr;
-                } else if let Ok(Record::Data { addr: _, value: _ }) = parse_result {
-                    self.current_addr += 1;
-                }
+    /// Returns the input position right after the last token read,
+    /// e.g. for progress reporting.
+    pub fn position(&self) -> Position {
+        self.token_iterator.position()
+    }
 
-                if self.options.group && !self.finished {
-                    while let Ok(Record::Data { addr, value }) = parse_result {
-                        if matches!(value, DataType::U64(_)) {
-                            break;
-                        }
-                        let start_addr = addr;
-                        if let Some(next_token) = self.token_iterator.peek() {
-                            let next_result = Record::from_string(next_token, self.current_addr);
-                            if let Ok(Record::Data {
-                                addr: _next_addr,
-                                value: next_value,
-                            }) = next_result
-                                && let DataType::U8(next_value_u8) = next_value
-                            {
-                                parse_result = Ok(Record::Data {
-                                    addr: start_addr,
-                                    value: group_new_data(value, next_value_u8),
-                                });
-                                self.current_addr += 1;
-                                self.token_iterator.next();
-                                continue;
-                            } else {
-                                break;
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                }
+    /// Returns number of input bytes read so far, see [`Reader::input_len`],
+    /// e.g. for a progress bar.
+    pub fn consumed_bytes(&self) -> usize {
+        self.token_iterator.position().offset
+    }
 
-                Some(parse_result)
-            }
-        }
+    /// Returns length of the whole input in bytes.
+    pub fn input_len(&self) -> usize {
+        self.token_iterator.position().offset + self.token_iterator.remaining()
     }
-}
 
-fn group_new_data(value: DataType, next_value_u8: u8) -> DataType {
-    match value {
-        DataType::U8(value_u8) => {
-            DataType::U16(u16::from(value_u8) | (u16::from(next_value_u8) << 8))
+    /// Private helper method collecting data tokens on the line of `first` into a
+    /// [`Record::DataLine`].
+    fn data_line(&mut self, first: Token<'a>) -> Record<'a> {
+        let mut end = first.offset + first.text.len();
+        let mut count = 1;
+        while let Some(next) = self.token_iterator.clone().next() {
+            if next.line != first.line
+                || !is_data_line_token(next, &self.options)
+                || !self.token_iterator.is_whitespace(end..next.offset)
+            {
+                break;
+            }
+            end = next.offset + next.text.len();
+            count += 1;
+            self.token_iterator.next();
         }
-        DataType::U16(value_u16) => {
-            DataType::U24(u32::from(value_u16) | (u32::from(next_value_u8) << 16))
+        let addr = self.current_addr;
+        self.current_addr += (count * self.options.token_bytes.clamp(1, 16)) as Addr;
+        Record::DataLine {
+            addr,
+            hex: self.token_iterator.text(first.offset..end),
         }
-        DataType::U24(value_u24) => DataType::U32(value_u24 | (u32::from(next_value_u8) << 24)),
-        DataType::U32(value_u32) => {
-            DataType::U40(u64::from(value_u32) | (u64::from(next_value_u8) << 32))
+    }
+
+    /// Returns iterator over records with every [`Record::DataLine`] decoded into
+    /// [`Record::Data`], for consumers of data that must not lose it.
+    pub(crate) fn data_records(self) -> DataRecords<'a> {
+        DataRecords {
+            reader: self,
+            line: None,
         }
-        DataType::U40(value_u40) => DataType::U48(value_u40 | (u64::from(next_value_u8) << 40)),
-        DataType::U48(value_u48) => DataType::U56(value_u48 | (u64::from(next_value_u8) << 48)),
-        DataType::U56(value_u56) => DataType::U64(value_u56 | (u64::from(next_value_u8) << 56)),
-        DataType::U64(value_u64) => DataType::U64(value_u64),
     }
-}
 
-//impl<'a> FusedIterator for Reader<'a> {}
+    /// Private helper method returning a reader of `hex` of the [`Record::DataLine`] at
+    /// `addr` just returned, with the token format of this reader, and the line and
+    /// column of `hex` in the input.
+    fn data_line_reader(&self, addr: Addr, hex: &'a str) -> (Reader<'a>, usize, usize) {
+        let end = self.position();
+        let options = ReaderOptions {
+            strict: self.options.strict,
+            underscores: self.options.underscores,
+            token_bytes: self.options.token_bytes,
+            binary: self.options.binary,
+            delimiters: self.options.delimiters,
+            ..Default::default()
+        };
+        let mut reader = Reader::new_with_options(hex, options);
+        reader.current_addr = addr;
+        (reader, end.line, end.column - hex.len())
+    }
+
+    /// Private helper method parsing `token` at the current address and applying options.
+    fn parse(&self, token: Token<'a>) -> Result<Record<'a>, ReaderError> {
+        parse_token(token, self.current_addr, &self.options)
+    }
+
+    /// Private helper method for obtaining the next record string.
+    /// Does not respect the 'finished' flag.
+    /// It will return either the next record string to be read, or None if nothing is left to process.
+    fn next_record(&mut self) -> Option<Token<'a>> {
+        self.token_iterator.next()
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Record<'a>, ParseError<'a>>;
+
+    /// The upper bound is estimated from the remaining input length. The rest of
+    /// the input may be only comments or whitespace, so the lower bound is 1 with
+    /// [`ReaderOptions::end_of_file`] and 0 otherwise.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished {
+            return (0, Some(0));
+        }
+        // Every token but the last one is followed by whitespace.
+        let remaining = self.token_iterator.remaining();
+        let end_of_file = usize::from(self.options.end_of_file);
+        (end_of_file, Some(remaining.div_ceil(2) + end_of_file))
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let (token, mut result) = loop {
+            let Some(token) = self.next_record() else {
+                self.finished = true;
+                return self.options.end_of_file.then_some(Ok(Record::EndOfFile));
+            };
+            detect_token_bytes(&mut self.options, token);
+            if self.options.data_lines && is_data_line_token(token, &self.options) {
+                return Some(Ok(self.data_line(token)));
+            }
+            let mut result = self.parse(token);
+            match skip_outside_window(&mut result, &self.options) {
+                Some(len) => self.current_addr += len as Addr,
+                None => break (token, result),
+            }
+        };
+        check_budget(&mut result, &self.options, &mut self.data_bytes);
+        check_order(&mut result, &self.options, self.current_addr);
+        let mut parse_result = result.map_err(|kind| ParseError {
+            kind,
+            line: token.line,
+            column: token.column,
+            token: token.text,
+        });
+
+        if parse_result.is_err() && !self.options.recover {
+            self.finished = true;
+        }
+
+        if let Ok(Record::EndOfFile) = parse_result {
+            self.finished = true;
+        }
+
+        if let Ok(Record::NewAddress(new_addr)) = parse_result {
+            self.current_addr = new_addr;
+        } else if let Ok(Record::Data { addr: _, value }) = parse_result {
+            self.current_addr += split_data(value).1 as Addr;
+        } else if let Ok(Record::Unknown { .. }) = parse_result {
+            self.current_addr += 1;
+        }
+
+        if self.options.group && !self.finished {
+            let group_bytes = self.options.group_bytes.clamp(1, 16);
+            while let Ok(Record::Data { addr, value }) = parse_result {
+                if split_data(value).1 >= group_bytes
+                    || (self.options.align_groups
+                        && self.current_addr.is_multiple_of(group_bytes as Addr))
+                {
+                    break;
+                }
+                let start_addr = addr;
+                if let Some(next_token) = self.token_iterator.clone().next() {
+                    if self.options.group_within_line && next_token.line != token.line {
+                        break;
+                    }
+                    let next_result = self.parse(next_token);
+                    if let Ok(Record::Data {
+                        addr: _next_addr,
+                        value: next_value,
+                    }) = next_result
+                        && let DataType::U8(next_value_u8) = next_value
+                        && in_window(&self.options, self.current_addr, 1)
+                        && within_budget(&self.options, self.current_addr, 1, self.data_bytes)
+                    {
+                        parse_result = Ok(Record::Data {
+                            addr: start_addr,
+                            value: group_new_data(value, next_value_u8),
+                        });
+                        self.current_addr += 1;
+                        self.data_bytes += 1;
+                        self.token_iterator.next();
+                        continue;
+                    } else {
+                        break;
+                    }
+                } else {
+                    break;
+                }
+            }
+        }
+
+        Some(parse_result)
+    }
+}
+
+/// Unsigned integer read by [`Reader::words`] and `MemoryMap::words`.
+pub trait Word: Copy {
+    /// Width in bytes.
+    const BYTES: usize;
+
+    /// Build the value from exactly [`Word::BYTES`] bytes in `endian` order.
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {$(
+        impl Word for $t {
+            const BYTES: usize = core::mem::size_of::<$t>();
+
+            fn from_bytes(bytes: &[u8], endian: Endian) -> Self {
+                let mut array = [0; core::mem::size_of::<$t>()];
+                array.copy_from_slice(bytes);
+                match endian {
+                    Endian::Little => <$t>::from_le_bytes(array),
+                    Endian::Big => <$t>::from_be_bytes(array),
+                }
+            }
+        }
+    )*};
+}
+
+impl_word!(u8, u16, u32, u64);
+
+/// Saved [`Reader`] state, see [`Reader::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a> {
+    token_iterator: Tokenizer<'a>,
+    finished: bool,
+    current_addr: Addr,
+    data_bytes: usize,
+}
+
+/// Iterator over data bytes of a [`Reader`], see [`Reader::bytes`].
+#[derive(Clone)]
+pub struct Bytes<'a> {
+    reader: DataRecords<'a>,
+    /// Address, value and length of the rest of the current grouped value.
+    addr: Addr,
+    value: u128,
+    len: usize,
+}
+
+impl<'a> Iterator for Bytes<'a> {
+    type Item = Result<(Addr, u8), ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.len == 0 {
+            match self.reader.next()? {
+                Ok(Record::Data { addr, value }) => {
+                    (self.value, self.len) = split_data(value);
+                    self.addr = addr;
+                }
+                Ok(_) => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        let byte = (self.addr, self.value as u8);
+        self.addr += 1;
+        self.value >>= 8;
+        self.len -= 1;
+        Some(Ok(byte))
+    }
+}
+
+/// Iterator over records of a [`Reader`], see [`Reader::data_records`].
+#[derive(Clone)]
+pub(crate) struct DataRecords<'a> {
+    reader: Reader<'a>,
+    /// Reader of the current data line with its line and first column in the input.
+    line: Option<(Reader<'a>, usize, usize)>,
+}
+
+impl<'a> Iterator for DataRecords<'a> {
+    type Item = Result<Record<'a>, ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((reader, line, column)) = &mut self.line {
+                match reader.next() {
+                    Some(Ok(record)) => return Some(Ok(record)),
+                    Some(Err(error)) => {
+                        if !self.reader.options.recover {
+                            self.reader.finished = true;
+                        }
+                        return Some(Err(ParseError {
+                            line: *line,
+                            column: *column + error.column - 1,
+                            ..error
+                        }));
+                    }
+                    None => self.line = None,
+                }
+            }
+            match self.reader.next()? {
+                Ok(Record::DataLine { addr, hex }) => {
+                    self.line = Some(self.reader.data_line_reader(addr, hex));
+                }
+                record => return Some(record),
+            }
+        }
+    }
+}
+
+/// Iterator over one byte lane of a [`Reader`], see [`Reader::lane`].
+#[derive(Clone)]
+pub struct Lane<'a> {
+    bytes: Bytes<'a>,
+    stride: Addr,
+    lane: Addr,
+}
+
+impl<'a> Iterator for Lane<'a> {
+    type Item = Result<(Addr, u8), ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.bytes.next()? {
+                Ok((addr, value)) if addr % self.stride == self.lane => {
+                    return Some(Ok((addr / self.stride, value)));
+                }
+                Ok(_) => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Iterator over contiguous runs of a [`Reader`], see [`Reader::runs`].
+pub struct Runs<'a> {
+    bytes: Bytes<'a>,
+}
+
+/// Contiguous run of data bytes, iterates over the bytes.
+///
+/// The bytes are parsed again from the start of the run when iterated.
+#[derive(Clone)]
+pub struct Run<'a> {
+    /// Address of the first byte.
+    pub start: Addr,
+    /// Number of bytes.
+    pub len: Addr,
+    bytes: Bytes<'a>,
+    remaining: Addr,
+}
+
+impl Iterator for Run<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        match self.bytes.next() {
+            Some(Ok((_, value))) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Iterator for Runs<'a> {
+    type Item = Result<Run<'a>, ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let run_bytes = self.bytes.clone();
+        let (start, _) = match self.bytes.next()? {
+            Ok(byte) => byte,
+            Err(error) => return Some(Err(error)),
+        };
+        let mut len = 1;
+        loop {
+            let before = self.bytes.clone();
+            match self.bytes.next() {
+                Some(Ok((addr, _))) if Some(addr) == start.checked_add(len) => len += 1,
+                _ => {
+                    // Leave the next run or the error for the next call.
+                    self.bytes = before;
+                    break;
+                }
+            }
+        }
+        Some(Ok(Run {
+            start,
+            len,
+            bytes: run_bytes,
+            remaining: len,
+        }))
+    }
+}
+
+/// Iterator over aligned words of a [`Reader`], see [`Reader::words`].
+pub struct Words<'a, T: Word> {
+    bytes: Bytes<'a>,
+    endian: Endian,
+    fill: u8,
+    /// Byte read past the end of the previous word.
+    peeked: Option<Result<(Addr, u8), ParseError<'a>>>,
+    word: core::marker::PhantomData<T>,
+}
+
+impl<'a, T: Word> Iterator for Words<'a, T> {
+    type Item = Result<(Addr, T), ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut last, first) = match self.peeked.take().or_else(|| self.bytes.next())? {
+            Ok(byte) => byte,
+            Err(error) => return Some(Err(error)),
+        };
+        let width = T::BYTES as Addr;
+        let start = last - last % width;
+        let mut word = [self.fill; 8];
+        word[(last - start) as usize] = first;
+        loop {
+            match self.bytes.next() {
+                Some(Ok((addr, value))) if addr > last && addr - start < width => {
+                    word[(addr - start) as usize] = value;
+                    last = addr;
+                }
+                next => {
+                    self.peeked = next;
+                    break;
+                }
+            }
+        }
+        Some(Ok((start, T::from_bytes(&word[..T::BYTES], self.endian))))
+    }
+}
+
+/// Returns byte address of `@` record address `addr` after
+/// [`ReaderOptions::addr_unit`] and [`ReaderOptions::remap`], `None` on overflow.
+fn map_addr(addr: Addr, options: &ReaderOptions) -> Option<Addr> {
+    let addr = options.addr_unit.to_byte_addr(addr)?;
+    match options
+        .remap
+        .iter()
+        .find(|(range, _)| range.contains(&addr))
+    {
+        Some((range, target)) => target.checked_add(addr - range.start),
+        None => Some(addr),
+    }
+}
+
+/// Returns `true` if `token` goes into a [`Record::DataLine`].
+fn is_data_line_token(token: Token, options: &ReaderOptions) -> bool {
+    !token.non_ascii
+        && !token.text.is_empty()
+        && !token.text.starts_with(['@', '/'])
+        && comment_text(token.text, options).is_none()
+}
+
+/// Returns comment text if `text` starts with `//` or one of
+/// [`ReaderOptions::comment_prefixes`].
+fn comment_text<'a>(text: &'a str, options: &ReaderOptions) -> Option<&'a str> {
+    text.strip_prefix("//").or_else(|| {
+        options
+            .comment_prefixes
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix).filter(|_| !prefix.is_empty()))
+    })
+}
+
+/// Longest number token without underscores, `@` and 128 binary digits.
+const MAX_NUMBER_LEN: usize = 1 + 128;
+
+/// Parse `token` at `current_addr` applying `options`, grouping aside.
+pub(crate) fn parse_token<'a>(
+    token: Token<'a>,
+    current_addr: Addr,
+    options: &ReaderOptions,
+) -> Result<Record<'a>, ReaderError> {
+    // Complete block comments never reach here.
+    if token.text.starts_with("/*") {
+        return Err(ReaderError::UnterminatedComment);
+    }
+    if let Some(text) = comment_text(token.text, options) {
+        if options.symbols
+            && let Some(symbol) = parse_symbol(text)
+        {
+            return Ok(symbol);
+        }
+        return Ok(Record::Comment(text));
+    }
+    if token.non_ascii {
+        return Err(ReaderError::NonAscii);
+    }
+    let mut buffer = [0; MAX_NUMBER_LEN];
+    let text = if options.underscores && token.text.contains('_') {
+        let mut len = 0;
+        for byte in token.text.bytes().filter(|byte| *byte != b'_') {
+            *buffer
+                .get_mut(len)
+                .ok_or(ReaderError::BadNumberConversion)? = byte;
+            len += 1;
+        }
+        str::from_utf8(&buffer[..len]).map_err(|_| ReaderError::NonAscii)?
+    } else {
+        token.text
+    };
+    if options.xz_digits
+        && !options.binary
+        && !text.starts_with('@')
+        && text.contains(['x', 'X', 'z', 'Z'])
+    {
+        return parse_xz(text, current_addr);
+    }
+    let token_bytes = options.token_bytes.clamp(1, 16);
+    let mut result = if (token_bytes > 1 || options.binary) && !text.starts_with('@') {
+        parse_word(text, current_addr, token_bytes, options.binary)
+    } else {
+        parse_value(text, current_addr)
+    };
+    if options.strict
+        && text.len() != digits_per_byte(options) * token_bytes
+        && matches!(result, Ok(Record::Data { .. }))
+    {
+        return Err(ReaderError::BadTokenWidth);
+    }
+    if let Ok(Record::NewAddress(addr)) = result {
+        return map_addr(addr, options)
+            .map(Record::NewAddress)
+            .ok_or(ReaderError::BadNumberConversion);
+    }
+    if let Ok(Record::Data { addr: _, value }) = &mut result {
+        let (word, len) = split_data(*value);
+        let mut bytes = word.to_le_bytes();
+        for byte in &mut bytes[..len] {
+            *byte = options.transform.apply(*byte);
+        }
+        *value = join_data(u128::from_le_bytes(bytes), len);
+    }
+    result
+}
+
+/// Apply [`ReaderOptions::detect_token_bytes`] if `token` is the first data token.
+pub(crate) fn detect_token_bytes(options: &mut ReaderOptions, token: Token) {
+    if !options.detect_token_bytes
+        || token.text.starts_with(['@', '/'])
+        || comment_text(token.text, options).is_some()
+    {
+        return;
+    }
+    let mut digits = token.text.len();
+    if options.underscores {
+        digits -= token.text.matches('_').count();
+    }
+    options.detect_token_bytes = false;
+    options.token_bytes = digits.div_ceil(digits_per_byte(options)).clamp(1, 16);
+    options.strict = true;
+}
+
+/// Returns `true` if `len` bytes at `addr` are within [`ReaderOptions::window`].
+pub(crate) fn in_window(options: &ReaderOptions, addr: Addr, len: usize) -> bool {
+    options
+        .window
+        .as_ref()
+        .is_none_or(|window| window.contains(&addr) && window.contains(&(addr + (len as Addr - 1))))
+}
+
+/// Returns address and length of data in `result`.
+fn data_span(result: &Result<Record<'_>, ReaderError>) -> Option<(Addr, usize)> {
+    match result {
+        Ok(Record::Data { addr, value }) => Some((*addr, split_data(*value).1)),
+        Ok(Record::Unknown { addr, .. }) => Some((*addr, 1)),
+        _ => None,
+    }
+}
+
+/// Apply [`ReaderOptions::window`] to a parsed record,
+/// returns number of bytes to skip if it is data outside of the window.
+pub(crate) fn skip_outside_window(
+    result: &mut Result<Record<'_>, ReaderError>,
+    options: &ReaderOptions,
+) -> Option<usize> {
+    let (addr, len) = data_span(result)?;
+    if in_window(options, addr, len) {
+        None
+    } else if options.window_error {
+        *result = Err(ReaderError::OutsideWindow);
+        None
+    } else {
+        Some(len)
+    }
+}
+
+/// Returns `true` if `len` more bytes at `addr` after `data_bytes` bytes of data
+/// fit into [`ReaderOptions::max_addr`] and [`ReaderOptions::max_bytes`].
+pub(crate) fn within_budget(
+    options: &ReaderOptions,
+    addr: Addr,
+    len: usize,
+    data_bytes: usize,
+) -> bool {
+    options.max_addr.is_none_or(|max_addr| {
+        addr.checked_add(len as Addr - 1)
+            .is_some_and(|end| end <= max_addr)
+    }) && options
+        .max_bytes
+        .is_none_or(|max_bytes| data_bytes + len <= max_bytes)
+}
+
+/// Apply [`ReaderOptions::max_addr`] and [`ReaderOptions::max_bytes`] to a parsed record
+/// and count its bytes in `data_bytes`.
+pub(crate) fn check_budget(
+    result: &mut Result<Record<'_>, ReaderError>,
+    options: &ReaderOptions,
+    data_bytes: &mut usize,
+) {
+    let Some((addr, len)) = data_span(result) else {
+        return;
+    };
+    if within_budget(options, addr, len, *data_bytes) {
+        *data_bytes += len;
+    } else {
+        *result = Err(ReaderError::OutOfRange);
+    }
+}
+
+/// Apply [`ReaderOptions::ascending`] to a parsed record at `current_addr`.
+pub(crate) fn check_order(
+    result: &mut Result<Record<'_>, ReaderError>,
+    options: &ReaderOptions,
+    current_addr: Addr,
+) {
+    if options.ascending
+        && let Ok(Record::NewAddress(addr)) = result
+        && *addr < current_addr
+    {
+        *result = Err(ReaderError::DescendingAddress);
+    }
+}
+
+/// Returns number of data token digits per byte.
+fn digits_per_byte(options: &ReaderOptions) -> usize {
+    if options.binary { 8 } else { 2 }
+}
+
+/// Parse data token `text` of up to `len` bytes into a `len`-byte value.
+fn parse_word(
+    text: &str,
+    addr: Addr,
+    len: usize,
+    binary: bool,
+) -> Result<Record<'static>, ReaderError> {
+    if text.is_empty() {
+        return Err(ReaderError::InvalidSyntax);
+    }
+    let (radix, digits) = if binary { (2, 8) } else { (16, 2) };
+    match u128::from_str_radix(text, radix) {
+        Ok(value) if text.len() <= digits * len && !text.starts_with('+') => Ok(Record::Data {
+            addr,
+            value: join_data(value, len),
+        }),
+        _ => Err(ReaderError::BadNumberConversion),
+    }
+}
+
+/// Parse comment `text` like ` 81000000 <_start>:` into [`Record::Symbol`].
+fn parse_symbol(text: &str) -> Option<Record<'_>> {
+    let (addr, name) = text.trim().split_once(' ')?;
+    let name = name.trim_start().strip_prefix('<')?.strip_suffix(">:")?;
+    Some(Record::Symbol {
+        addr: Addr::from_str_radix(addr, 16).ok()?,
+        name,
+    })
+}
+
+/// Parse data token `text` with `x` or `z` digits into [`Record::Unknown`].
+fn parse_xz(text: &str, addr: Addr) -> Result<Record<'static>, ReaderError> {
+    if text.len() > 2 {
+        return Err(ReaderError::BadNumberConversion);
+    }
+    let (mut value, mut mask) = (0, 0);
+    for digit in text.chars() {
+        let (digit, unknown) = match digit {
+            'x' | 'X' | 'z' | 'Z' => (0, 0xF),
+            _ => (
+                digit.to_digit(16).ok_or(ReaderError::BadNumberConversion)? as u8,
+                0,
+            ),
+        };
+        value = (value << 4) | digit;
+        mask = (mask << 4) | unknown;
+    }
+    Ok(Record::Unknown { addr, value, mask })
+}
+
+pub(crate) fn group_new_data(value: DataType, next_value_u8: u8) -> DataType {
+    match split_data(value) {
+        (_, 16) => value,
+        (value, len) => join_data(value | (u128::from(next_value_u8) << (8 * len)), len + 1),
+    }
+}
+
+/// Returns grouped value and its length in bytes.
+pub(crate) fn split_data(value: DataType) -> (u128, usize) {
+    let word = DataWord::from(value);
+    (word.value(), word.bytes())
+}
+
+/// Returns `len` bytes of `value` as the matching grouped value, `len` is 1..=16.
+pub(crate) fn join_data(value: u128, len: usize) -> DataType {
+    DataType::from(DataWord::new(value, len))
+}
+
+/// Once [`Reader`] returns `None`, after the end of input, the end-of-file record
+/// or an error that stops reading, it keeps returning `None`.
+impl FusedIterator for Reader<'_> {}
 
 #[cfg(feature = "std")]
 pub fn read_file(filepath: &str) -> Option<std::string::String> {
@@ -336,6 +1850,42 @@ pub fn read_file(filepath: &str) -> Option<std::string::String> {
     }
 }
 
+/// File mapped into memory, see [`map_file`].
+#[cfg(feature = "mmap")]
+pub struct MappedFile {
+    mmap: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    /// Returns the file contents.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Create a new reader of the file with default options.
+    pub fn reader(&self) -> Reader<'_> {
+        Reader::from_bytes(&self.mmap)
+    }
+
+    /// Create a new reader of the file with the specified options.
+    pub fn reader_with_options<'a>(&'a self, options: ReaderOptions<'a>) -> Reader<'a> {
+        Reader::from_bytes_with_options(&self.mmap, options)
+    }
+}
+
+/// Map file at `filepath` into memory instead of reading it like [`read_file`],
+/// e.g. for multi-gigabyte memory dumps. Requires the `mmap` feature.
+///
+/// The file must not be modified while it is mapped.
+#[cfg(feature = "mmap")]
+pub fn map_file(filepath: &str) -> std::io::Result<MappedFile> {
+    let file = std::fs::File::open(filepath)?;
+    // SAFETY: the mapping is read-only and the caller keeps the file unchanged.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    Ok(MappedFile { mmap })
+}
+
 #[cfg(test)]
 static TEXT_STR: &str = r#"
 @81000000
@@ -380,13 +1930,1058 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_to_verilog_syntax() {
+        use std::string::ToString;
+
+        let records = crate::Reader::new(TEXT_STR).map(Result::unwrap);
+        let text = records
+            .map(|record| record.to_verilog_syntax().to_string())
+            .collect::<std::vec::Vec<_>>()
+            .join(" ");
+        assert!(crate::Reader::new(&text).eq(crate::Reader::new(TEXT_STR)));
+
+        let record = Record::Data {
+            addr: 0,
+            value: DataType::U16(0xABCD),
+        };
+        let syntax = record.to_verilog_syntax().hex_case(HexCase::Lower);
+        assert_eq!(syntax.to_string(), "abcd");
+
+        let mut reader = crate::Reader::new("@10 //<main>:\n0A");
+        let comment = reader.nth(1).unwrap().unwrap();
+        assert_eq!(comment, Record::Comment("<main>:"));
+        assert_eq!(comment.to_verilog_syntax().to_string(), "//<main>:");
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut reader = crate::Reader::new_with_options(
+            "@10 01 A5",
+            crate::ReaderOptions {
+                transform: ByteTransform::BitReverse,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            reader.nth(1),
+            Some(Ok(Record::Data {
+                addr: 0x10,
+                value: DataType::U8(0x80)
+            }))
+        );
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x11,
+                value: DataType::U8(0xA5)
+            }))
+        );
+        assert_eq!(ByteTransform::NibbleSwap.apply(0x1F), 0xF1);
+    }
+
     #[test]
     fn test_read_group() {
-        let reader =
-            crate::Reader::new_with_options(TEXT_STR, crate::ReaderOptions { group: true });
+        let reader = crate::Reader::new_with_options(
+            TEXT_STR,
+            crate::ReaderOptions {
+                group: true,
+                ..Default::default()
+            },
+        );
         for _data in reader {
             #[cfg(feature = "std")]
             std::println!("{}", _data.unwrap());
         }
     }
+
+    #[test]
+    fn test_read_group_bytes() {
+        let mut reader = crate::Reader::new_with_options(
+            TEXT_STR,
+            crate::ReaderOptions {
+                group: true,
+                group_bytes: 4,
+                ..Default::default()
+            },
+        );
+        assert_eq!(reader.next(), Some(Ok(Record::NewAddress(0x81000000))));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x81000000,
+                value: DataType::U32(0x22F3A009)
+            }))
+        );
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x81000004,
+                value: DataType::U32(0x84633420)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_read_align_groups() {
+        let options = crate::ReaderOptions {
+            group: true,
+            group_bytes: 4,
+            align_groups: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@2 01 02 03 04 05 06 07", options);
+        reader.next();
+        let values: [_; 3] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            values,
+            [
+                Some(Ok(Record::Data {
+                    addr: 2,
+                    value: DataType::U16(0x0201)
+                })),
+                Some(Ok(Record::Data {
+                    addr: 4,
+                    value: DataType::U32(0x06050403)
+                })),
+                Some(Ok(Record::Data {
+                    addr: 8,
+                    value: DataType::U8(0x07)
+                })),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_group_within_line() {
+        let options = crate::ReaderOptions {
+            group: true,
+            group_within_line: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@0\n01 02 03\n04 05\n", options);
+        reader.next();
+        let values: [_; 3] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            values,
+            [
+                Some(Ok(Record::Data {
+                    addr: 0,
+                    value: DataType::U24(0x030201)
+                })),
+                Some(Ok(Record::Data {
+                    addr: 3,
+                    value: DataType::U16(0x0504)
+                })),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_group_128() {
+        let options = crate::ReaderOptions {
+            group: true,
+            group_bytes: 16,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options(TEXT_STR, options);
+        reader.next();
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x81000000,
+                value: DataType::U128(0x408157E0006F00028463342022F3A009)
+            }))
+        );
+        assert_eq!(split_data(join_data(0xABCDEF, 3)), (0xABCDEF, 3));
+    }
+
+    #[test]
+    fn test_read_strict() {
+        let options = crate::ReaderOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 0A F 0B", options);
+        reader.next();
+        assert!(matches!(reader.next(), Some(Ok(Record::Data { .. }))));
+        assert_eq!(
+            reader.next(),
+            Some(Err(ParseError {
+                kind: ReaderError::BadTokenWidth,
+                line: 1,
+                column: 8,
+                token: "F"
+            }))
+        );
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_read_recover() {
+        let options = crate::ReaderOptions {
+            recover: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 XX 0A @YY 0B", options);
+        reader.next();
+        let values: [_; 5] = core::array::from_fn(|_| reader.next().map(|r| r.map_err(|e| e.kind)));
+        assert_eq!(
+            values,
+            [
+                Some(Err(ReaderError::BadNumberConversion)),
+                Some(Ok(Record::Data {
+                    addr: 0x10,
+                    value: DataType::U8(0x0A)
+                })),
+                Some(Err(ReaderError::BadNumberConversion)),
+                Some(Ok(Record::Data {
+                    addr: 0x11,
+                    value: DataType::U8(0x0B)
+                })),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_parse_error_location() {
+        use std::string::ToString;
+
+        let error = crate::Reader::new("@10\n\n  0A _0123456789012345678901234567890123456789")
+            .find_map(Result::err)
+            .map(OwnedParseError::from)
+            .unwrap();
+        assert_eq!((error.line, error.column), (3, 6));
+        assert_eq!(error.token(), "_0123456789012345678901234567890");
+        assert_eq!(
+            error.to_string(),
+            "3:6: cant convert string to number at `_0123456789012345678901234567890`"
+        );
+
+        let error = crate::Reader::new("@10\n0A 0B\n  0C X1 0D")
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!((error.line, error.column, error.token), (3, 6, "X1"));
+        assert_eq!(
+            error.to_string(),
+            "3:6: cant convert string to number at `X1`"
+        );
+    }
+
+    #[test]
+    fn test_data_type_accessors() {
+        let value = DataType::U24(0x0A0B0C);
+        assert_eq!(
+            (value.as_u64(), value.as_u128()),
+            (Some(0x0A0B0C), 0x0A0B0C)
+        );
+        assert_eq!((value.byte_len(), value.width_bits()), (3, 24));
+        let value = DataType::U72(0x01_0000_0000_0000_0000);
+        assert_eq!(
+            (value.as_u64(), value.byte_len(), value.width_bits()),
+            (None, 9, 72)
+        );
+        assert_eq!(DataType::U72(5).as_u64(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "std"))]
+    fn test_serde() {
+        let records: std::vec::Vec<_> = crate::Reader::new("@10 // x\n0A 0X").collect();
+        let json = serde_json::to_string(&records).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"Ok":{"NewAddress":16}},{"Ok":{"Comment":" x"}},{"Ok":{"Data":{"addr":16,"value":{"U8":10}}}},{"Err":{"kind":"BadNumberConversion","line":2,"column":4,"token":"0X"}}]"#
+        );
+        let parsed: std::vec::Vec<Result<Record, ParseError>> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, records);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hex_formatting() {
+        use std::format;
+
+        let value = DataType::U16(0xAB);
+        assert_eq!(
+            format!("{value:x} {value:#X} {value:6x}"),
+            "00ab 0x00AB     ab"
+        );
+        assert_eq!(format!("{:b}", DataType::U8(5)), "00000101");
+        let record = Record::Data {
+            addr: 0x8100000A,
+            value,
+        };
+        assert_eq!(format!("{record:x}"), "0x8100000a: 00ab");
+        assert_eq!(format!("{record:X}"), "0x8100000A: 00AB");
+        assert_eq!(format!("{record:b}"), "0x8100000A: 0000000010101011");
+        let record = Record::Unknown {
+            addr: 0x10,
+            value: 0x05,
+            mask: 0xF0,
+        };
+        assert_eq!(
+            format!("{record:x} {record:b}"),
+            "0x00000010: x5 0x00000010: xxxx0101"
+        );
+    }
+
+    #[test]
+    fn test_data_word() {
+        let word = DataWord::new(0xFF0A0B0C, 3);
+        assert_eq!((word.value(), word.bytes()), (0x0A0B0C, 3));
+        assert_eq!(DataType::from(word), DataType::U24(0x0A0B0C));
+        for len in 1..=16 {
+            let value = join_data(u128::MAX, len);
+            assert_eq!(DataType::from(DataWord::from(value)), value);
+            assert_eq!(DataWord::from(value), DataWord::new(u128::MAX, len));
+        }
+        assert_eq!(DataWord::new(1, 0).bytes(), 1);
+    }
+
+    #[test]
+    fn test_data_type_to_bytes() {
+        let value = DataType::U24(0x0A0B0C);
+        assert_eq!(*value.to_le_bytes(), [0x0C, 0x0B, 0x0A]);
+        assert_eq!(*value.to_be_bytes(), [0x0A, 0x0B, 0x0C]);
+        let mut buffer = [0; 4];
+        buffer[1..].copy_from_slice(&value.to_bytes(Endian::Big));
+        assert_eq!(buffer, [0, 0x0A, 0x0B, 0x0C]);
+        assert_eq!(DataType::U128(1).to_be_bytes().last(), Some(&1));
+    }
+
+    #[test]
+    fn test_data_type_try_from() {
+        assert_eq!(u32::try_from(DataType::U24(0x0A0B0C)), Ok(0x0A0B0C));
+        assert_eq!(u64::try_from(DataType::U8(7)), Ok(7));
+        assert_eq!(
+            u16::try_from(DataType::U24(1)),
+            Err(DataWidthError { bits: 24 })
+        );
+        assert_eq!(u128::from(DataType::U72(1 << 64)), 1 << 64);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let options = crate::ReaderOptions {
+            group: true,
+            ..Default::default()
+        };
+        let mut bytes = crate::Reader::new_with_options(TEXT_STR, options).bytes();
+        assert_eq!(bytes.next(), Some(Ok((0x81000000, 0x09))));
+        assert_eq!(bytes.next(), Some(Ok((0x81000001, 0xA0))));
+        assert_eq!(bytes.count(), 158);
+        let mut bytes = crate::Reader::new("@10 // 0B\n0A 0X").bytes();
+        assert_eq!(bytes.next(), Some(Ok((0x10, 0x0A))));
+        assert!(matches!(bytes.next(), Some(Err(_))));
+        assert_eq!(bytes.next(), None);
+    }
+
+    #[test]
+    fn test_words() {
+        let mut words = crate::Reader::new(TEXT_STR).words::<u32>(Endian::Little, 0);
+        assert_eq!(words.next(), Some(Ok((0x81000000, 0x22F3A009))));
+        assert_eq!(words.count(), 39);
+        let mut words = crate::Reader::new("@3 01 02 @10 03").words::<u16>(Endian::Big, 0xFF);
+        assert_eq!(words.next(), Some(Ok((2, 0xFF01))));
+        assert_eq!(words.next(), Some(Ok((4, 0x02FF))));
+        assert_eq!(words.next(), Some(Ok((0x10, 0x03FF))));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_lane() {
+        let mut lane = crate::Reader::new("@10 00 01 02 03 04 05 @21 06 0X").lane(4, 1);
+        assert_eq!(lane.next(), Some(Ok((4, 0x01))));
+        assert_eq!(lane.next(), Some(Ok((5, 0x05))));
+        assert_eq!(lane.next(), Some(Ok((8, 0x06))));
+        assert!(matches!(lane.next(), Some(Err(_))));
+        assert_eq!(lane.next(), None);
+    }
+
+    #[test]
+    fn test_runs() {
+        let mut runs = crate::Reader::new("@10 01 02 03 @20 04 0X").runs();
+        let run = runs.next().unwrap().unwrap();
+        assert_eq!((run.start, run.len), (0x10, 3));
+        assert!(run.eq([1, 2, 3]));
+        let run = runs.next().unwrap().unwrap();
+        assert_eq!((run.start, run.len), (0x20, 1));
+        assert!(matches!(runs.next(), Some(Err(_))));
+        assert!(runs.next().is_none());
+
+        let mut runs = crate::Reader::new(TEXT_STR).runs();
+        assert_eq!(runs.next().map(|run| run.unwrap().len), Some(160));
+    }
+
+    #[test]
+    fn test_fused() {
+        fn assert_fused<I: FusedIterator>(_: &I) {}
+
+        let mut reader = crate::Reader::new("@10 0A 0X 0B");
+        assert_fused(&reader);
+        assert!(reader.nth(2).unwrap().is_err());
+        assert_eq!(reader.next(), None);
+        assert_eq!(reader.next(), None);
+        let mut reader = crate::Reader::new("0A");
+        assert!(reader.next().is_some());
+        assert_eq!(reader.next(), None);
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let mut reader = crate::Reader::new("@10 0A 0B");
+        assert_eq!(reader.size_hint(), (0, Some(5)));
+        reader.next();
+        assert_eq!(reader.size_hint(), (0, Some(3)));
+        assert_eq!(reader.by_ref().count(), 2);
+        assert_eq!(reader.size_hint(), (0, Some(0)));
+        let (_, upper) = crate::Reader::new(TEXT_STR).size_hint();
+        assert!(upper.unwrap() >= 161);
+
+        for options in [
+            Default::default(),
+            crate::ReaderOptions {
+                end_of_file: true,
+                ..Default::default()
+            },
+        ] {
+            let text = "// a long comment line here that has no data at all\n0A\n/* 0B */ \n";
+            let reader = crate::Reader::new_with_options(text, options);
+            let (lower, upper) = reader.size_hint();
+            let count = reader.count();
+            assert!(lower <= count && count <= upper.unwrap());
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let mut reader = crate::Reader::new("@10 0A 0B @20 0C");
+        reader.nth(1);
+        let checkpoint = reader.checkpoint();
+        let mut ahead = reader.clone();
+        assert_eq!(ahead.nth(1), Some(Ok(Record::NewAddress(0x20))));
+        assert_eq!(reader.by_ref().count(), 3);
+        reader.restore(checkpoint);
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x11,
+                value: DataType::U8(0x0B)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_peek_record() {
+        let mut reader = crate::Reader::new("@10 0A");
+        assert_eq!(reader.peek_record(), Some(Ok(Record::NewAddress(0x10))));
+        assert_eq!(reader.next(), Some(Ok(Record::NewAddress(0x10))));
+        let data = Some(Ok(Record::Data {
+            addr: 0x10,
+            value: DataType::U8(0x0A),
+        }));
+        assert_eq!(reader.peek_record(), data);
+        assert_eq!(reader.next(), data);
+        assert_eq!(reader.peek_record(), None);
+    }
+
+    #[test]
+    fn test_seek_to() {
+        let mut reader = crate::Reader::new(TEXT_STR);
+        assert!(reader.seek_to(0x81000081));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x81000081,
+                value: DataType::U8(0x71)
+            }))
+        );
+        let mut reader = crate::Reader::new("@10 XX 0A @30 0B @20 0C");
+        assert!(reader.seek_to(0x20));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x30,
+                value: DataType::U8(0x0B)
+            }))
+        );
+        assert!(!reader.seek_to(0x40));
+        assert_eq!(reader.next(), None);
+
+        // 0x16 is in the middle of the second token, which must not be skipped.
+        let options = ReaderOptions {
+            token_bytes: 4,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 00000001 00000002", options);
+        assert!(reader.seek_to(0x16));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x14,
+                value: DataType::U32(2)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_addr_unit() {
+        let options = crate::ReaderOptions {
+            addr_unit: AddrUnit::Words(4),
+            ..Default::default()
+        };
+        let mut reader =
+            crate::Reader::new_with_options("@10 0A @40000000000000000000000000000000", options);
+        assert_eq!(reader.next(), Some(Ok(Record::NewAddress(0x40))));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x40,
+                value: DataType::U8(0x0A)
+            }))
+        );
+        assert_eq!(
+            reader.next().map(|r| r.map_err(|e| e.kind)),
+            Some(Err(ReaderError::BadNumberConversion))
+        );
+    }
+
+    #[test]
+    fn test_underscores() {
+        let options = crate::ReaderOptions {
+            underscores: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@8100_0000 0_A", options);
+        assert_eq!(reader.next(), Some(Ok(Record::NewAddress(0x81000000))));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x81000000,
+                value: DataType::U8(0x0A)
+            }))
+        );
+        let mut reader = crate::Reader::new("@8100_0000");
+        assert!(reader.next().unwrap().is_err());
+
+        let options = crate::ReaderOptions {
+            underscores: true,
+            binary: true,
+            token_bytes: 16,
+            ..Default::default()
+        };
+        let mut text = [b'1'; 128 + 15];
+        for index in (8..text.len()).step_by(9) {
+            text[index] = b'_';
+        }
+        let text = str::from_utf8(&text).unwrap();
+        let mut reader = crate::Reader::new_with_options(text, options);
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0,
+                value: DataType::U128(u128::MAX)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_xz_digits() {
+        let options = crate::ReaderOptions {
+            xz_digits: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 xx 1z 0A", options);
+        reader.next();
+        let values: [_; 3] = core::array::from_fn(|_| reader.next().unwrap().unwrap());
+        assert_eq!(
+            values,
+            [
+                Record::Unknown {
+                    addr: 0x10,
+                    value: 0,
+                    mask: 0xFF
+                },
+                Record::Unknown {
+                    addr: 0x11,
+                    value: 0x10,
+                    mask: 0x0F
+                },
+                Record::Data {
+                    addr: 0x12,
+                    value: DataType::U8(0x0A)
+                },
+            ]
+        );
+        #[cfg(feature = "std")]
+        {
+            use std::string::ToString;
+            assert_eq!(values[1].to_verilog_syntax().to_string(), "1X");
+        }
+        assert!(crate::Reader::new("xx").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_block_comments() {
+        let mut reader = crate::Reader::new("@10 /* 0A\n 0B */ 0C /* 0D");
+        reader.next();
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x10,
+                value: DataType::U8(0x0C)
+            }))
+        );
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            (error.kind, error.line, error.column),
+            (ReaderError::UnterminatedComment, 2, 11)
+        );
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_token_bytes() {
+        let options = ReaderOptions {
+            token_bytes: 4,
+            strict: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 04030201 0807_0605", options.clone());
+        let records: [_; 3] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x10))),
+                Some(Ok(Record::Data {
+                    addr: 0x10,
+                    value: DataType::U32(0x04030201)
+                })),
+                Some(Err(ParseError {
+                    kind: ReaderError::BadNumberConversion,
+                    line: 1,
+                    column: 14,
+                    token: "0807_0605"
+                })),
+            ]
+        );
+
+        let mut reader = crate::Reader::new_with_options("@10 04030201 08070605 0A", options);
+        assert!(reader.seek_to(0x14));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x14,
+                value: DataType::U32(0x08070605)
+            }))
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().kind,
+            ReaderError::BadTokenWidth
+        );
+    }
+
+    #[test]
+    fn test_detect_token_bytes() {
+        let options = ReaderOptions {
+            detect_token_bytes: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 //0A\n0201 0403 05", options);
+        let records: [_; 5] =
+            core::array::from_fn(|_| reader.next().map(|r| r.map_err(|e| e.kind)));
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x10))),
+                Some(Ok(Record::Comment("0A"))),
+                Some(Ok(Record::Data {
+                    addr: 0x10,
+                    value: DataType::U16(0x0201)
+                })),
+                Some(Ok(Record::Data {
+                    addr: 0x12,
+                    value: DataType::U16(0x0403)
+                })),
+                Some(Err(ReaderError::BadTokenWidth)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary() {
+        let options = ReaderOptions {
+            binary: true,
+            underscores: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 1010_0101 11 2", options);
+        let records: [_; 4] =
+            core::array::from_fn(|_| reader.next().map(|r| r.map_err(|e| e.kind)));
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x10))),
+                Some(Ok(Record::Data {
+                    addr: 0x10,
+                    value: DataType::U8(0xA5)
+                })),
+                Some(Ok(Record::Data {
+                    addr: 0x11,
+                    value: DataType::U8(0x03)
+                })),
+                Some(Err(ReaderError::BadNumberConversion)),
+            ]
+        );
+
+        let options = ReaderOptions {
+            binary: true,
+            detect_token_bytes: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("0000000100000010 1", options);
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0,
+                value: DataType::U16(0x0102)
+            }))
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().kind,
+            ReaderError::BadTokenWidth
+        );
+    }
+
+    #[test]
+    fn test_window() {
+        let text = "@10 0A 0B 0C 0D 0E";
+        let options = ReaderOptions {
+            group: true,
+            window: Some(0x11..=0x12),
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options(text, options.clone());
+        let records: [_; 3] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x10))),
+                Some(Ok(Record::Data {
+                    addr: 0x11,
+                    value: DataType::U16(0x0C0B)
+                })),
+                None
+            ]
+        );
+
+        let mut reader = crate::Reader::new_with_options(
+            text,
+            ReaderOptions {
+                window_error: true,
+                ..options
+            },
+        );
+        assert_eq!(
+            reader.nth(1).unwrap().unwrap_err().kind,
+            ReaderError::OutsideWindow
+        );
+    }
+
+    #[test]
+    fn test_budget() {
+        let text = "@10 0A 0B 0C @0 0D";
+        let options = ReaderOptions {
+            group: true,
+            max_addr: Some(0x11),
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options(text, options);
+        assert_eq!(
+            reader.nth(1),
+            Some(Ok(Record::Data {
+                addr: 0x10,
+                value: DataType::U16(0x0B0A)
+            }))
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().kind,
+            ReaderError::OutOfRange
+        );
+        assert_eq!(reader.next(), None);
+
+        let options = ReaderOptions {
+            max_bytes: Some(3),
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options(text, options);
+        assert_eq!(
+            reader.nth(5).unwrap().unwrap_err().kind,
+            ReaderError::OutOfRange
+        );
+    }
+
+    #[test]
+    fn test_data_lines() {
+        let options = ReaderOptions {
+            data_lines: true,
+            ..Default::default()
+        };
+        let text = "@10 0A 0B  0C\n0D /*c*/ 0E //x\n0F";
+        let mut reader = crate::Reader::new_with_options(text, options.clone());
+        let records: [_; 7] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x10))),
+                Some(Ok(Record::DataLine {
+                    addr: 0x10,
+                    hex: "0A 0B  0C"
+                })),
+                Some(Ok(Record::DataLine {
+                    addr: 0x13,
+                    hex: "0D"
+                })),
+                Some(Ok(Record::DataLine {
+                    addr: 0x14,
+                    hex: "0E"
+                })),
+                Some(Ok(Record::Comment("x"))),
+                Some(Ok(Record::DataLine {
+                    addr: 0x15,
+                    hex: "0F"
+                })),
+                None
+            ]
+        );
+
+        let reader = crate::Reader::new_with_options(text, options.clone());
+        assert!(
+            reader
+                .bytes()
+                .map(Result::unwrap)
+                .eq(crate::Reader::new(text).bytes().map(Result::unwrap))
+        );
+
+        let mut bytes = crate::Reader::new_with_options("@10\n01  0X 02", options).bytes();
+        assert_eq!(bytes.next(), Some(Ok((0x10, 0x01))));
+        let error = bytes.next().unwrap().unwrap_err();
+        assert_eq!((error.line, error.column, error.token), (2, 5, "0X"));
+        assert_eq!(bytes.next(), None);
+    }
+
+    #[test]
+    fn test_comment_prefixes() {
+        // A local array, not a `'static` one.
+        let prefixes = ["#", "--"];
+        let options = ReaderOptions {
+            comment_prefixes: &prefixes,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("#boot\n@10 --x\n0A ;y", options);
+        let records: [_; 5] =
+            core::array::from_fn(|_| reader.next().map(|r| r.map_err(|e| e.kind)));
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::Comment("boot"))),
+                Some(Ok(Record::NewAddress(0x10))),
+                Some(Ok(Record::Comment("x"))),
+                Some(Ok(Record::Data {
+                    addr: 0x10,
+                    value: DataType::U8(0x0A)
+                })),
+                Some(Err(ReaderError::BadNumberConversion)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ascending() {
+        let options = ReaderOptions {
+            ascending: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 0A @11 0B @11", options.clone());
+        assert_eq!(
+            reader.nth(4).unwrap().unwrap_err().kind,
+            ReaderError::DescendingAddress
+        );
+        let mut reader = crate::Reader::new_with_options("@10 @8", options);
+        assert_eq!(
+            reader.nth(1).unwrap().unwrap_err().kind,
+            ReaderError::DescendingAddress
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        let options = ReaderOptions {
+            symbols: true,
+            ..Default::default()
+        };
+        let text = "// 81000000 <_start>:\n@81000000 09\n// 81000004 <main> at\n";
+        let mut reader = crate::Reader::new_with_options(text, options);
+        let records: [_; 5] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::Symbol {
+                    addr: 0x81000000,
+                    name: "_start"
+                })),
+                Some(Ok(Record::NewAddress(0x81000000))),
+                Some(Ok(Record::Data {
+                    addr: 0x81000000,
+                    value: DataType::U8(0x09)
+                })),
+                Some(Ok(Record::Comment(" 81000004 <main> at"))),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_consumed_bytes() {
+        let mut reader = crate::Reader::new("@10 0A 0B\n");
+        assert_eq!((reader.consumed_bytes(), reader.input_len()), (0, 10));
+        reader.nth(1);
+        assert_eq!(reader.consumed_bytes(), 6);
+        reader.by_ref().count();
+        assert_eq!(reader.consumed_bytes(), 10);
+    }
+
+    #[test]
+    fn test_end_of_file() {
+        let options = ReaderOptions {
+            end_of_file: true,
+            ..Default::default()
+        };
+        let mut reader = crate::Reader::new_with_options("@10 0A", options.clone());
+        let records: [_; 4] =
+            core::array::from_fn(|_| reader.next().map(|r| r.map_err(|e| e.kind)));
+        assert_eq!(records[2..], [Some(Ok(Record::EndOfFile)), None]);
+
+        let mut reader = crate::Reader::new_with_options("@10 0X", options);
+        assert!(reader.nth(1).unwrap().is_err());
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_last_address() {
+        let reader = crate::Reader::new("@20 0A 0B // 0C\n@10 0D");
+        assert_eq!(reader.last_address(), Some(0x21));
+        assert_eq!(crate::Reader::new("@20 // 0A").last_address(), None);
+
+        let options = ReaderOptions {
+            token_bytes: 4,
+            ..Default::default()
+        };
+        let reader = crate::Reader::new_with_options("@10 04030201 @1X 0A", options);
+        assert_eq!(reader.last_address(), Some(0x13));
+    }
+
+    #[test]
+    fn test_remap() {
+        // A table built at run time, not a `'static` one.
+        let remap = [(0..0x1_0000, 0x8100_0000), (0x1_0000..0x2_0000, 0)];
+        let options = ReaderOptions {
+            remap: &remap,
+            ..Default::default()
+        };
+        let text = "@10 0A @10000 0B @20000";
+        let mut reader = crate::Reader::new_with_options(text, options.clone());
+        let records: [_; 5] = core::array::from_fn(|_| reader.next());
+        assert_eq!(
+            records,
+            [
+                Some(Ok(Record::NewAddress(0x8100_0010))),
+                Some(Ok(Record::Data {
+                    addr: 0x8100_0010,
+                    value: DataType::U8(0x0A)
+                })),
+                Some(Ok(Record::NewAddress(0))),
+                Some(Ok(Record::Data {
+                    addr: 0,
+                    value: DataType::U8(0x0B)
+                })),
+                Some(Ok(Record::NewAddress(0x2_0000))),
+            ]
+        );
+        let mut reader = crate::Reader::new_with_options(text, options);
+        assert!(reader.seek_to(0x8100_0000));
+        assert_eq!(reader.last_address(), Some(0x8100_0010));
+    }
+
+    #[test]
+    fn test_position() {
+        let mut reader = crate::Reader::new_with_options(
+            "@10\n0A 0B\n0C",
+            crate::ReaderOptions {
+                group: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            reader.position(),
+            Position {
+                line: 1,
+                column: 1,
+                offset: 0
+            }
+        );
+        reader.next();
+        reader.next();
+        assert_eq!(
+            reader.position(),
+            Position {
+                line: 3,
+                column: 3,
+                offset: 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        let mut reader = crate::Reader::from_bytes(b"@10 0A //\xFF\n0\xC9 \xFF");
+        reader.next();
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Record::Data {
+                addr: 0x10,
+                value: DataType::U8(0x0A)
+            }))
+        );
+        assert_eq!(reader.next(), Some(Ok(Record::Comment(""))));
+        assert_eq!(
+            reader.next().map(|r| r.map_err(|e| (e.kind, e.column))),
+            Some(Err((ReaderError::NonAscii, 1)))
+        );
+        let mut reader = crate::Reader::from_bytes(b"\xFF");
+        assert_eq!(
+            reader.next().map(|r| r.map_err(|e| e.kind)),
+            Some(Err(ReaderError::NonAscii))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_map_file() {
+        let path = std::env::temp_dir().join("veriloghex_test_map_file.hex");
+        std::fs::write(&path, TEXT_STR).unwrap();
+        let file = crate::map_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(file.as_bytes(), TEXT_STR.as_bytes());
+        assert!(file.reader().eq(crate::Reader::new(TEXT_STR)));
+        drop(file);
+        std::fs::remove_file(path).unwrap();
+        assert!(crate::map_file("/nonexistent/file.hex").is_err());
+    }
 }
@@ -0,0 +1,287 @@
+//! Fixed-capacity memory image that does not need `alloc`.
+//!
+//! # Loading example:
+//!
+//! ```ignore
+//! let image = crate::MemoryImage::<4096, 8>::from_reader(crate::Reader::new(TEXT_STR))?;
+//! assert_eq!(image.get(0x81000000), Some(0x09));
+//! ```
+
+use core::error::Error;
+use core::fmt;
+
+use crate::{Addr, OwnedParseError, ParseError, Reader, Record, split_data};
+
+/// Error of loading data into a [`MemoryImage`] or a buffer, see [`load_into`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImageError {
+    /// Failed to parse input.
+    Reader(OwnedParseError),
+    /// No space left for the byte at `addr`.
+    DataCapacity { addr: Addr },
+    /// No segment left for the byte at `addr`.
+    SegmentCapacity { addr: Addr },
+    /// Address `addr` is outside of the buffer.
+    OutOfRange { addr: Addr },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Reader(error) => write!(f, "read error: {error}"),
+            ImageError::DataCapacity { addr } => {
+                write!(f, "no space left for data at address {addr:#010X}")
+            }
+            ImageError::SegmentCapacity { addr } => {
+                write!(f, "no segment left for data at address {addr:#010X}")
+            }
+            ImageError::OutOfRange { addr } => {
+                write!(f, "address {addr:#010X} is out of buffer range")
+            }
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+impl From<OwnedParseError> for ImageError {
+    fn from(error: OwnedParseError) -> Self {
+        ImageError::Reader(error)
+    }
+}
+
+impl From<ParseError<'_>> for ImageError {
+    fn from(error: ParseError<'_>) -> Self {
+        ImageError::Reader(error.into())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Segment {
+    addr: Addr,
+    offset: usize,
+    len: usize,
+}
+
+impl Segment {
+    fn contains(&self, addr: Addr) -> bool {
+        addr >= self.addr && addr - self.addr < self.len as Addr
+    }
+}
+
+/// Sparse memory image with room for `N` bytes in at most `SEGS` contiguous segments.
+///
+/// Bytes are stored in the order they are first written, so a file with
+/// increasing addresses fills the storage without waste.
+/// Touching segments are not merged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryImage<const N: usize, const SEGS: usize> {
+    data: [u8; N],
+    len: usize,
+    /// Sorted by address, only `segment_count` first are valid.
+    segments: [Segment; SEGS],
+    segment_count: usize,
+}
+
+impl<const N: usize, const SEGS: usize> Default for MemoryImage<N, SEGS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const SEGS: usize> MemoryImage<N, SEGS> {
+    /// Create an empty image.
+    pub const fn new() -> Self {
+        MemoryImage {
+            data: [0; N],
+            len: 0,
+            segments: [Segment {
+                addr: 0,
+                offset: 0,
+                len: 0,
+            }; SEGS],
+            segment_count: 0,
+        }
+    }
+
+    /// Create an image with all the data of `reader`.
+    pub fn from_reader(reader: Reader<'_>) -> Result<Self, ImageError> {
+        let mut image = Self::new();
+        image.load(reader)?;
+        Ok(image)
+    }
+
+    /// Add all the data of `reader`, later data overwrites earlier data.
+    pub fn load(&mut self, reader: Reader<'_>) -> Result<(), ImageError> {
+        for record in reader.data_records() {
+            if let Record::Data { addr, value } = record? {
+                let (value, len) = split_data(value);
+                for (i, byte) in value.to_le_bytes()[..len].iter().enumerate() {
+                    self.write_u8(addr + i as Addr, *byte)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Set byte at `addr`.
+    pub fn write_u8(&mut self, addr: Addr, value: u8) -> Result<(), ImageError> {
+        let index = self.segments[..self.segment_count].partition_point(|s| s.addr <= addr);
+        if let Some(segment) = index.checked_sub(1).map(|i| &mut self.segments[i]) {
+            if segment.contains(addr) {
+                self.data[segment.offset + (addr - segment.addr) as usize] = value;
+                return Ok(());
+            }
+            // Grow the segment if its data is at the end of the storage.
+            if addr - segment.addr == segment.len as Addr
+                && segment.offset + segment.len == self.len
+            {
+                let slot = self
+                    .data
+                    .get_mut(self.len)
+                    .ok_or(ImageError::DataCapacity { addr })?;
+                *slot = value;
+                segment.len += 1;
+                self.len += 1;
+                return Ok(());
+            }
+        }
+
+        if self.segment_count == SEGS {
+            return Err(ImageError::SegmentCapacity { addr });
+        }
+        let slot = self
+            .data
+            .get_mut(self.len)
+            .ok_or(ImageError::DataCapacity { addr })?;
+        *slot = value;
+        self.segments
+            .copy_within(index..self.segment_count, index + 1);
+        self.segments[index] = Segment {
+            addr,
+            offset: self.len,
+            len: 1,
+        };
+        self.segment_count += 1;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns byte at `addr`, `None` if it is not initialized.
+    pub fn get(&self, addr: Addr) -> Option<u8> {
+        self.segments()
+            .find(|(start, bytes)| addr >= *start && addr - start < bytes.len() as Addr)
+            .map(|(start, bytes)| bytes[(addr - start) as usize])
+    }
+
+    /// Returns iterator over segments as `(start address, bytes)` in address order.
+    pub fn segments(&self) -> impl Iterator<Item = (Addr, &[u8])> {
+        self.segments[..self.segment_count]
+            .iter()
+            .map(|s| (s.addr, &self.data[s.offset..s.offset + s.len]))
+    }
+
+    /// Returns number of initialized bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.len
+    }
+
+    /// Returns number of segments.
+    pub fn segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Returns `true` if no byte is initialized.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Write all the data of `reader` into `buf` that holds memory starting at `base_addr`,
+/// e.g. a RAM window of the target.
+///
+/// Returns number of bytes written and fails with [`ImageError::OutOfRange`]
+/// on the first byte that does not fit into `buf`.
+pub fn load_into(reader: Reader<'_>, base_addr: Addr, buf: &mut [u8]) -> Result<usize, ImageError> {
+    let mut count = 0;
+    for record in reader.data_records() {
+        if let Record::Data { addr, value } = record? {
+            let (value, len) = split_data(value);
+            for (i, byte) in value.to_le_bytes()[..len].iter().enumerate() {
+                let addr = addr + i as Addr;
+                let slot = addr
+                    .checked_sub(base_addr)
+                    .and_then(|offset| usize::try_from(offset).ok())
+                    .and_then(|offset| buf.get_mut(offset))
+                    .ok_or(ImageError::OutOfRange { addr })?;
+                *slot = *byte;
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_reader() {
+        let image = MemoryImage::<256, 2>::from_reader(Reader::new(crate::TEXT_STR)).unwrap();
+        assert_eq!(image.segment_count(), 1);
+        assert_eq!(image.total_bytes(), 160);
+        assert_eq!(image.get(0x81000000), Some(0x09));
+        assert_eq!(image.get(0x81000081), Some(0x71));
+        assert_eq!(image.get(0x810000A0), None);
+
+        let options = crate::ReaderOptions {
+            data_lines: true,
+            ..Default::default()
+        };
+        let reader = Reader::new_with_options(crate::TEXT_STR, options);
+        assert_eq!(MemoryImage::<256, 2>::from_reader(reader), Ok(image));
+
+        assert_eq!(
+            MemoryImage::<100, 2>::from_reader(Reader::new(crate::TEXT_STR)),
+            Err(ImageError::DataCapacity { addr: 0x81000064 })
+        );
+    }
+
+    #[test]
+    fn test_load_into() {
+        let mut buf = [0; 0x100];
+        assert_eq!(
+            load_into(Reader::new(crate::TEXT_STR), 0x80FFFFF0, &mut buf),
+            Ok(160)
+        );
+        assert_eq!(buf[0x10], 0x09);
+
+        assert_eq!(
+            load_into(Reader::new(crate::TEXT_STR), 0x81000010, &mut buf),
+            Err(ImageError::OutOfRange { addr: 0x81000000 })
+        );
+        assert_eq!(
+            load_into(Reader::new(crate::TEXT_STR), 0x81000000, &mut buf[..0x80]),
+            Err(ImageError::OutOfRange { addr: 0x81000080 })
+        );
+    }
+
+    #[test]
+    fn test_write_u8() {
+        let mut image = MemoryImage::<8, 2>::new();
+        image.write_u8(0x20, 0xAA).unwrap();
+        image.write_u8(0x10, 0xBB).unwrap();
+        image.write_u8(0x11, 0xCC).unwrap();
+        image.write_u8(0x20, 0xDD).unwrap();
+        let mut segments = image.segments();
+        assert_eq!(segments.next(), Some((0x10, &[0xBB, 0xCC][..])));
+        assert_eq!(segments.next(), Some((0x20, &[0xDD][..])));
+        assert_eq!(segments.next(), None);
+        drop(segments);
+        assert_eq!(
+            image.write_u8(0x30, 0),
+            Err(ImageError::SegmentCapacity { addr: 0x30 })
+        );
+    }
+}
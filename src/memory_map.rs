@@ -0,0 +1,1545 @@
+//! Sparse memory image built from a [`crate::Reader`].
+//!
+//! # Loading example:
+//!
+//! ```ignore
+//! let map = crate::MemoryMap::from_reader(crate::Reader::new(TEXT_STR))?;
+//! assert_eq!(map.get(0x81000000), Some(0x09));
+//! for (addr, value) in map.bytes() {
+//!     std::println!("{addr:#010X}: {value:02X}");
+//! }
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+use core::ops::Range;
+
+use crate::{
+    Addr, AddrOffset, Checksum, ChecksumAlgorithm, Digest, Endian, OwnedParseError, ParseError,
+    Reader, Record, Word, split_data,
+};
+
+/// What to do with data written to an already initialized address.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum ConflictPolicy {
+    /// Fail with [`MergeError::Conflict`].
+    #[default]
+    Error,
+    /// Keep the data that was there first.
+    FirstWins,
+    /// Overwrite with the new data.
+    LastWins,
+}
+
+/// Error of merging several images into one [`MemoryMap`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MergeError {
+    /// Failed to parse input.
+    Reader(OwnedParseError),
+    /// Address `addr` is initialized by more than one image.
+    Conflict { addr: Addr },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeError::Reader(error) => write!(f, "read error: {error}"),
+            MergeError::Conflict { addr } => {
+                write!(f, "address {addr:#010X} is initialized more than once")
+            }
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+impl From<OwnedParseError> for MergeError {
+    fn from(error: OwnedParseError) -> Self {
+        MergeError::Reader(error)
+    }
+}
+
+impl From<ParseError<'_>> for MergeError {
+    fn from(error: ParseError<'_>) -> Self {
+        MergeError::Reader(error.into())
+    }
+}
+
+/// Error of [`MemoryMap`] operations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MapError {
+    /// Data would be moved outside of the address space.
+    AddressOverflow,
+    /// New region overlaps an existing region at `addr`.
+    RegionOverlap { addr: Addr },
+    /// Byte at `addr` is not initialized.
+    Uninitialized { addr: Addr },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::AddressOverflow => write!(f, "address out of address space"),
+            MapError::RegionOverlap { addr } => {
+                write!(f, "region overlaps another region at {addr:#010X}")
+            }
+            MapError::Uninitialized { addr } => {
+                write!(f, "address {addr:#010X} is not initialized")
+            }
+        }
+    }
+}
+
+impl Error for MapError {}
+
+/// One difference between two images, see [`MemoryMap::diff`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Difference {
+    /// Byte at `addr` is initialized in both images with different values.
+    Changed { addr: Addr, old: u8, new: u8 },
+    /// Byte at `addr` is initialized only in the left (`self`) image.
+    OnlyInLeft { addr: Addr, value: u8 },
+    /// Byte at `addr` is initialized only in the right (`other`) image.
+    OnlyInRight { addr: Addr, value: u8 },
+}
+
+/// Byte statistics of an image, see [`MemoryMap::stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// Number of occurrences of every byte value.
+    pub histogram: [u64; 256],
+    /// Shannon entropy in bits per byte of every window, keyed by window start address.
+    pub window_entropy: Vec<(Addr, f64)>,
+}
+
+impl Stats {
+    /// Returns the most common byte value, the lowest one on a tie.
+    pub fn most_common(&self) -> Option<u8> {
+        let (value, count) = self
+            .histogram
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| **count)?;
+        (*count > 0).then_some(value as u8)
+    }
+
+    /// Returns Shannon entropy of the whole image in bits per byte, 0..=8.
+    pub fn entropy(&self) -> f64 {
+        entropy(&self.histogram)
+    }
+}
+
+/// Shannon entropy in bits per byte of data with `histogram`.
+fn entropy(histogram: &[u64; 256]) -> f64 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    // H = log2(N) - sum(c * log2(c)) / N
+    let sum: f64 = histogram
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| *count as f64 * log2(*count as f64))
+        .sum();
+    (log2(total as f64) - sum / total as f64).max(0.0)
+}
+
+/// Base 2 logarithm of positive `x`, `core` has no float math.
+fn log2(x: f64) -> f64 {
+    // x = m * 2^e with m in [1, 2), ln(m) = 2 * atanh((m - 1) / (m + 1)).
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7FF) as i64 - 1023;
+    let mantissa = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    let t = (mantissa - 1.0) / (mantissa + 1.0);
+    let t2 = t * t;
+    let mut term = t;
+    let mut ln = 0.0;
+    for k in 0..20 {
+        ln += term / (2 * k + 1) as f64;
+        term *= t2;
+    }
+    exponent as f64 + 2.0 * ln / core::f64::consts::LN_2
+}
+
+/// Named address range of a [`MemoryMap`], e.g. a linker section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// Region name, e.g. `.text`.
+    pub name: String,
+    /// Addresses that belong to the region.
+    pub range: Range<Addr>,
+}
+
+/// Sparse memory image stored as contiguous segments.
+///
+/// Segments never overlap and never touch, writing data next to a segment extends it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MemoryMap {
+    /// Segment data keyed by segment start address.
+    segments: BTreeMap<Addr, Vec<u8>>,
+    /// Named regions sorted by start address, see [`MemoryMap::add_region`].
+    regions: Vec<Region>,
+}
+
+impl MemoryMap {
+    /// Create a new empty map.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Load all data records of `reader` into a new map.
+    pub fn from_reader(reader: Reader<'_>) -> Result<Self, OwnedParseError> {
+        let mut map = MemoryMap::new();
+        map.load(reader)?;
+        Ok(map)
+    }
+
+    /// Load all data records of `reader`, later data overwrites earlier data at the same address.
+    pub fn load(&mut self, reader: Reader<'_>) -> Result<(), OwnedParseError> {
+        for record in reader.data_records() {
+            if let Record::Data { addr, value } = record? {
+                let (value, len) = split_data(value);
+                self.write_slice(addr, &value.to_le_bytes()[..len]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns byte at `addr`, `None` if it is not initialized.
+    pub fn get(&self, addr: Addr) -> Option<u8> {
+        let (start, bytes) = self.segments.range(..=addr).next_back()?;
+        bytes.get(usize::try_from(addr - start).ok()?).copied()
+    }
+
+    /// Returns `len` bytes starting at `addr`, `None` if any of them is not initialized.
+    pub fn read_slice(&self, addr: Addr, len: usize) -> Option<&[u8]> {
+        let (start, bytes) = self.segments.range(..=addr).next_back()?;
+        let offset = usize::try_from(addr - start).ok()?;
+        bytes.get(offset..offset.checked_add(len)?)
+    }
+
+    /// Returns byte at `addr`, `None` if it is not initialized.
+    pub fn read_u8(&self, addr: Addr) -> Option<u8> {
+        self.get(addr)
+    }
+
+    /// Returns 16-bit value at `addr`, `None` if any byte is not initialized.
+    pub fn read_u16(&self, addr: Addr, endian: Endian) -> Option<u16> {
+        let bytes = self.read_array(addr)?;
+        Some(match endian {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    /// Returns 32-bit value at `addr`, `None` if any byte is not initialized.
+    ///
+    /// ```ignore
+    /// let insn = map.read_u32(0x8100_0040, crate::Endian::Little);
+    /// ```
+    pub fn read_u32(&self, addr: Addr, endian: Endian) -> Option<u32> {
+        let bytes = self.read_array(addr)?;
+        Some(match endian {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    /// Returns 64-bit value at `addr`, `None` if any byte is not initialized.
+    pub fn read_u64(&self, addr: Addr, endian: Endian) -> Option<u64> {
+        let bytes = self.read_array(addr)?;
+        Some(match endian {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        })
+    }
+
+    fn read_array<const N: usize>(&self, addr: Addr) -> Option<[u8; N]> {
+        self.read_slice(addr, N)?.try_into().ok()
+    }
+
+    /// Iterate over `(address, word)` of every aligned word from the lowest to the highest
+    /// initialized address, uninitialized bytes read as `fill`,
+    /// e.g. to initialize a simulator memory array.
+    ///
+    /// ```ignore
+    /// for (addr, word) in map.words::<u32>(crate::Endian::Little, 0) { }
+    /// ```
+    pub fn words<T: Word>(&self, endian: Endian, fill: u8) -> impl Iterator<Item = (Addr, T)> + '_ {
+        let width = T::BYTES as Addr;
+        let bounds = self.min_addr().zip(self.max_addr());
+        let start = bounds.map_or(0, |(min, _)| min - min % width);
+        let count = bounds.map_or(0, |(_, max)| (max - start) / width + 1);
+        (0..count).map(move |i| {
+            let addr = start + i * width;
+            let mut bytes = [fill; 8];
+            for (offset, byte) in bytes[..T::BYTES].iter_mut().enumerate() {
+                if let Some(value) = self.get(addr + offset as Addr) {
+                    *byte = value;
+                }
+            }
+            (addr, T::from_bytes(&bytes[..T::BYTES], endian))
+        })
+    }
+
+    /// Returns the lowest initialized address.
+    pub fn min_addr(&self) -> Option<Addr> {
+        self.segments.keys().next().copied()
+    }
+
+    /// Returns the highest initialized address.
+    pub fn max_addr(&self) -> Option<Addr> {
+        let (start, bytes) = self.segments.iter().next_back()?;
+        Some(start + (bytes.len() as Addr - 1))
+    }
+
+    /// Returns number of initialized bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.segments.values().map(|bytes| bytes.len() as u64).sum()
+    }
+
+    /// Returns number of contiguous segments.
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` if the map holds no data.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Iterate over contiguous segments as `(start address, bytes)` in address order.
+    ///
+    /// ```ignore
+    /// for (addr, bytes) in map.segments() {
+    ///     flash.program(addr, bytes)?;
+    /// }
+    /// ```
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = (Addr, &[u8])> + '_ {
+        self.segments
+            .iter()
+            .map(|(start, bytes)| (*start, bytes.as_slice()))
+    }
+
+    /// Iterate over initialized `(address, byte)` pairs in address order,
+    /// e.g. to feed [`crate::Writer::write_bytes`].
+    pub fn bytes(&self) -> impl Iterator<Item = (Addr, u8)> + '_ {
+        self.segments.iter().flat_map(|(start, bytes)| {
+            bytes
+                .iter()
+                .enumerate()
+                .map(move |(i, value)| (start + i as Addr, *value))
+        })
+    }
+
+    /// Merge several images, e.g. a boot ROM and an application, into a new map.
+    ///
+    /// Returns the map and the address ranges initialized by more than one image,
+    /// resolved with `policy`.
+    ///
+    /// ```ignore
+    /// let (map, conflicts) = crate::MemoryMap::merge_readers(
+    ///     [crate::Reader::new(boot_rom), crate::Reader::new(app)],
+    ///     crate::ConflictPolicy::LastWins,
+    /// )?;
+    /// ```
+    pub fn merge_readers<'a, I>(
+        readers: I,
+        policy: ConflictPolicy,
+    ) -> Result<(Self, Vec<Range<Addr>>), MergeError>
+    where
+        I: IntoIterator<Item = Reader<'a>>,
+    {
+        let mut map = MemoryMap::new();
+        let mut conflicts = Vec::new();
+        for reader in readers {
+            conflicts.append(&mut map.merge(&MemoryMap::from_reader(reader)?, policy)?);
+        }
+        Ok((map, conflicts))
+    }
+
+    /// Merge `other` into this map and return the address ranges initialized in both,
+    /// resolved with `policy`.
+    ///
+    /// With [`ConflictPolicy::Error`] the map is left unchanged when any conflict is found.
+    pub fn merge(
+        &mut self,
+        other: &MemoryMap,
+        policy: ConflictPolicy,
+    ) -> Result<Vec<Range<Addr>>, MergeError> {
+        if policy == ConflictPolicy::Error
+            && let Some(conflict) = other
+                .segments
+                .iter()
+                .find_map(|(start, bytes)| self.overlaps(*start, bytes).first().cloned())
+        {
+            return Err(MergeError::Conflict {
+                addr: conflict.start,
+            });
+        }
+
+        let mut conflicts = Vec::new();
+        for (start, bytes) in &other.segments {
+            for conflict in self.write_slice_with_policy(*start, bytes, policy)? {
+                push_range(&mut conflicts, conflict);
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Load all data records of `reader` and return the address ranges
+    /// it initializes more than once, resolved with `policy`.
+    ///
+    /// ```ignore
+    /// let overwritten = map.load_with_policy(reader, crate::ConflictPolicy::LastWins)?;
+    /// ```
+    pub fn load_with_policy(
+        &mut self,
+        reader: Reader<'_>,
+        policy: ConflictPolicy,
+    ) -> Result<Vec<Range<Addr>>, MergeError> {
+        let mut conflicts = Vec::new();
+        for record in reader.data_records() {
+            if let Record::Data { addr, value } = record? {
+                let (value, len) = split_data(value);
+                let data = &value.to_le_bytes()[..len];
+                for conflict in self.write_slice_with_policy(addr, data, policy)? {
+                    push_range(&mut conflicts, conflict);
+                }
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Store `data` at `addr` and return the already initialized address ranges
+    /// it overlaps, resolved with `policy`.
+    ///
+    /// With [`ConflictPolicy::Error`] nothing is written when any overlap is found.
+    /// Bytes past `Addr::MAX` are dropped like in [`MemoryMap::write_slice`], and
+    /// an overlap at `Addr::MAX` is reported as the empty range `Addr::MAX..Addr::MAX`.
+    pub fn write_slice_with_policy(
+        &mut self,
+        addr: Addr,
+        data: &[u8],
+        policy: ConflictPolicy,
+    ) -> Result<Vec<Range<Addr>>, MergeError> {
+        let data = clip_to_addr_space(addr, data);
+        let conflicts = self.overlaps(addr, data);
+
+        match policy {
+            ConflictPolicy::Error => {
+                if let Some(conflict) = conflicts.first() {
+                    return Err(MergeError::Conflict {
+                        addr: conflict.start,
+                    });
+                }
+                self.write_slice(addr, data);
+            }
+            ConflictPolicy::LastWins => self.write_slice(addr, data),
+            ConflictPolicy::FirstWins => {
+                let top_conflict = conflicts.last().is_some_and(Range::is_empty);
+                let mut hole_start = addr;
+                for conflict in conflicts.iter().filter(|conflict| !conflict.is_empty()) {
+                    let offset = (hole_start - addr) as usize;
+                    let len = (conflict.start - hole_start) as usize;
+                    self.write_slice(hole_start, &data[offset..offset + len]);
+                    hole_start = conflict.end;
+                }
+                let offset = (hole_start - addr) as usize;
+                let end = data.len() - usize::from(top_conflict);
+                self.write_slice(hole_start, &data[offset..end]);
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Returns initialized address ranges that writing `data` at `addr` overlaps,
+    /// `data` must fit into the address space.
+    fn overlaps(&self, addr: Addr, data: &[u8]) -> Vec<Range<Addr>> {
+        let end = addr.saturating_add(data.len() as Addr);
+        let mut overlaps: Vec<Range<Addr>> = self.initialized_in(addr..end).collect();
+        // No `Range` includes `Addr::MAX`, an overlap there is reported as an empty range.
+        if !data.is_empty()
+            && addr.checked_add(data.len() as Addr).is_none()
+            && self.get(Addr::MAX).is_some()
+        {
+            overlaps.push(Addr::MAX..Addr::MAX);
+        }
+        overlaps
+    }
+
+    /// Returns initialized address ranges intersecting `range`, none if it is empty.
+    fn initialized_in(&self, range: Range<Addr>) -> impl Iterator<Item = Range<Addr>> + '_ {
+        let first = self
+            .segments
+            .range(..=range.start)
+            .next_back()
+            .map_or(range.start, |(start, _)| *start);
+        self.segments
+            .range(first..range.end.max(first))
+            .map(move |(start, bytes)| {
+                // Saturates for a segment ending at `Addr::MAX`, beyond any `range.end`.
+                let end = start.saturating_add(bytes.len() as Addr);
+                (*start).max(range.start)..end.min(range.end)
+            })
+            .filter(|overlap| !overlap.is_empty())
+    }
+
+    /// Apply all `(address, bytes)` patches or none of them.
+    ///
+    /// Every patch is validated before anything is written: it must fit into the
+    /// address space and, with `overwrite_only`, may only change initialized bytes.
+    pub fn apply_patches(
+        &mut self,
+        patches: &[(Addr, &[u8])],
+        overwrite_only: bool,
+    ) -> Result<(), MapError> {
+        for (addr, bytes) in patches {
+            let Some(end) = addr.checked_add(bytes.len() as Addr) else {
+                return Err(MapError::AddressOverflow);
+            };
+            if overwrite_only {
+                let mut expected = *addr;
+                for piece in self.initialized_in(*addr..end) {
+                    if piece.start != expected {
+                        break;
+                    }
+                    expected = piece.end;
+                }
+                if expected != end {
+                    return Err(MapError::Uninitialized { addr: expected });
+                }
+            }
+        }
+        for (addr, bytes) in patches {
+            self.write_slice(*addr, bytes);
+        }
+        Ok(())
+    }
+
+    /// Store `value` at `addr`.
+    pub fn write_u8(&mut self, addr: Addr, value: u8) {
+        self.write_slice(addr, &[value]);
+    }
+
+    /// Iterate over uninitialized `(start, len)` ranges between the lowest and the highest
+    /// initialized address, e.g. to decide which flash sectors need erasing.
+    pub fn gaps(&self) -> impl Iterator<Item = (Addr, Addr)> + '_ {
+        let ends = self
+            .segments
+            .iter()
+            .map(|(start, bytes)| start.saturating_add(bytes.len() as Addr));
+        ends.zip(self.segments.keys().skip(1))
+            .map(|(end, next_start)| (end, next_start - end))
+    }
+
+    /// Returns a new map with only the data inside `range`, e.g. the bootloader region
+    /// of a combined image.
+    pub fn extract(&self, range: Range<Addr>) -> MemoryMap {
+        let mut map = MemoryMap::new();
+        for piece in self.initialized_in(range) {
+            if let Some(bytes) = self.read_slice(piece.start, (piece.end - piece.start) as usize) {
+                map.segments.insert(piece.start, bytes.to_vec());
+            }
+        }
+        map
+    }
+
+    /// Compare with `other` and iterate over differing bytes in address order,
+    /// e.g. to compare two firmware builds regardless of their text formatting.
+    pub fn diff<'m>(&'m self, other: &'m MemoryMap) -> impl Iterator<Item = Difference> + 'm {
+        let mut left = self.bytes().peekable();
+        let mut right = other.bytes().peekable();
+        core::iter::from_fn(move || {
+            loop {
+                let difference = match (left.peek().copied(), right.peek().copied()) {
+                    (None, None) => return None,
+                    (Some((addr, value)), None) => {
+                        left.next();
+                        Difference::OnlyInLeft { addr, value }
+                    }
+                    (None, Some((addr, value))) => {
+                        right.next();
+                        Difference::OnlyInRight { addr, value }
+                    }
+                    (Some((left_addr, old)), Some((right_addr, new))) => {
+                        if left_addr < right_addr {
+                            left.next();
+                            Difference::OnlyInLeft {
+                                addr: left_addr,
+                                value: old,
+                            }
+                        } else if right_addr < left_addr {
+                            right.next();
+                            Difference::OnlyInRight {
+                                addr: right_addr,
+                                value: new,
+                            }
+                        } else {
+                            left.next();
+                            right.next();
+                            if old == new {
+                                continue;
+                            }
+                            Difference::Changed {
+                                addr: left_addr,
+                                old,
+                                new,
+                            }
+                        }
+                    }
+                };
+                return Some(difference);
+            }
+        })
+    }
+
+    /// Iterate over addresses where `pattern` occurs, e.g. to locate magic numbers.
+    ///
+    /// Matches may overlap but never span uninitialized bytes, an empty pattern never matches.
+    pub fn find<'m>(&'m self, pattern: &'m [u8]) -> impl Iterator<Item = Addr> + 'm {
+        self.segments.iter().flat_map(move |(start, bytes)| {
+            let len = pattern.len().max(1);
+            bytes
+                .windows(len)
+                .enumerate()
+                .filter(move |(_, window)| *window == pattern)
+                .map(move |(offset, _)| start + offset as Addr)
+        })
+    }
+
+    /// Turn into an immutable [`FrozenMap`] that can be shared between threads.
+    /// Only on targets with pointer-sized atomics, which `Arc` needs.
+    #[cfg(target_has_atomic = "ptr")]
+    pub fn freeze(self) -> FrozenMap {
+        let mut starts = Vec::with_capacity(self.segments.len());
+        let mut offsets = Vec::with_capacity(self.segments.len() + 1);
+        let mut data = Vec::with_capacity(self.total_bytes() as usize);
+        for (start, bytes) in self.segments {
+            starts.push(start);
+            offsets.push(data.len());
+            data.extend_from_slice(&bytes);
+        }
+        offsets.push(data.len());
+        FrozenMap {
+            inner: Arc::new(FrozenSegments {
+                starts: starts.into(),
+                offsets: offsets.into(),
+                data: data.into(),
+            }),
+        }
+    }
+
+    /// Returns an [`Overlay`] with this map as the base layer.
+    pub fn overlay(&self) -> Overlay<'_> {
+        Overlay::new(self)
+    }
+
+    /// Register region `name` covering `range`, regions must not overlap.
+    pub fn add_region(&mut self, name: &str, range: Range<Addr>) -> Result<(), MapError> {
+        let index = self
+            .regions
+            .partition_point(|region| region.range.start < range.start);
+        let neighbours = self.regions[index.saturating_sub(1)..].iter().take(2);
+        for region in neighbours {
+            let start = region.range.start.max(range.start);
+            if start < region.range.end.min(range.end) {
+                return Err(MapError::RegionOverlap { addr: start });
+            }
+        }
+        self.regions.insert(
+            index,
+            Region {
+                name: String::from(name),
+                range,
+            },
+        );
+        Ok(())
+    }
+
+    /// Iterate over registered regions in address order.
+    pub fn regions(&self) -> impl Iterator<Item = &Region> {
+        self.regions.iter()
+    }
+
+    /// Returns the region `addr` belongs to.
+    pub fn region_of(&self, addr: Addr) -> Option<&Region> {
+        let index = self
+            .regions
+            .partition_point(|region| region.range.start <= addr);
+        let region = self.regions.get(index.checked_sub(1)?)?;
+        region.range.contains(&addr).then_some(region)
+    }
+
+    /// Iterate over regions with number of initialized bytes in each of them,
+    /// e.g. for a size report.
+    pub fn region_sizes(&self) -> impl Iterator<Item = (&Region, Addr)> {
+        self.regions.iter().map(|region| {
+            let size = self
+                .initialized_in(region.range.clone())
+                .map(|piece| piece.end - piece.start)
+                .sum();
+            (region, size)
+        })
+    }
+
+    /// Returns byte histogram and entropy of every `window` bytes of every segment,
+    /// e.g. to spot compressed or encrypted regions and padding.
+    pub fn stats(&self, window: usize) -> Stats {
+        let mut histogram = [0; 256];
+        let mut window_entropy = Vec::new();
+        for (start, bytes) in &self.segments {
+            bytes
+                .iter()
+                .for_each(|byte| histogram[usize::from(*byte)] += 1);
+            if window == 0 {
+                continue;
+            }
+            for (i, chunk) in bytes.chunks(window).enumerate() {
+                let mut chunk_histogram = [0; 256];
+                chunk
+                    .iter()
+                    .for_each(|byte| chunk_histogram[usize::from(*byte)] += 1);
+                window_entropy.push((start + (i * window) as Addr, entropy(&chunk_histogram)));
+            }
+        }
+        Stats {
+            histogram,
+            window_entropy,
+        }
+    }
+
+    /// Returns checksum or digest of the initialized bytes in address order,
+    /// e.g. for boot ROM validation and release manifests.
+    ///
+    /// ```ignore
+    /// let crc = map.checksum(crate::ChecksumAlgorithm::Crc32).to_string();
+    /// ```
+    pub fn checksum(&self, algorithm: ChecksumAlgorithm) -> Digest {
+        let mut checksum = Checksum::new(algorithm);
+        self.segments
+            .values()
+            .for_each(|bytes| checksum.update(bytes));
+        checksum.digest()
+    }
+
+    /// Returns a flat buffer from the lowest to the highest address with holes set to `fill`,
+    /// e.g. for emulators and flashing tools that want raw bytes.
+    pub fn to_vec(&self, fill: u8) -> Vec<u8> {
+        match (self.min_addr(), self.max_addr()) {
+            (Some(min), Some(max)) => {
+                // `max` is initialized and may be `Addr::MAX`, which no `Range` includes.
+                let mut buffer = self.to_vec_range(min..max, fill);
+                buffer.extend(self.get(max));
+                buffer
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns a flat buffer covering `range` with uninitialized bytes set to `fill`.
+    pub fn to_vec_range(&self, range: Range<Addr>, fill: u8) -> Vec<u8> {
+        let len = range.end.saturating_sub(range.start) as usize;
+        let mut buffer = alloc::vec![fill; len];
+        for piece in self.initialized_in(range.clone()) {
+            let len = (piece.end - piece.start) as usize;
+            if let Some(bytes) = self.read_slice(piece.start, len) {
+                let offset = (piece.start - range.start) as usize;
+                buffer[offset..offset + len].copy_from_slice(bytes);
+            }
+        }
+        buffer
+    }
+
+    /// Move all data so that the lowest address becomes `new_base`,
+    /// e.g. to base an image linked at 0x8100_0000 at 0.
+    pub fn rebase(&mut self, new_base: Addr) -> Result<(), MapError> {
+        match self.min_addr() {
+            Some(min) if new_base >= min => self.shift(true, new_base - min),
+            Some(min) => self.shift(false, min - new_base),
+            None => Ok(()),
+        }
+    }
+
+    /// Add `delta` to all addresses.
+    pub fn offset(&mut self, delta: AddrOffset) -> Result<(), MapError> {
+        self.shift(delta >= 0, delta.unsigned_abs())
+    }
+
+    /// Move all data `up` or down by `amount`.
+    fn shift(&mut self, up: bool, amount: Addr) -> Result<(), MapError> {
+        if let (Some(min), Some(max)) = (self.min_addr(), self.max_addr())
+            && (if up {
+                max.checked_add(amount).is_none()
+            } else {
+                min < amount
+            })
+        {
+            return Err(MapError::AddressOverflow);
+        }
+        self.segments = core::mem::take(&mut self.segments)
+            .into_iter()
+            .map(|(start, bytes)| {
+                let start = if up { start + amount } else { start - amount };
+                (start, bytes)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Initialize every gap between the lowest and the highest address with `value`,
+    /// e.g. 0xFF for a flash-ready image.
+    pub fn fill_gaps(&mut self, value: u8) {
+        if let (Some(min), Some(max)) = (self.min_addr(), self.max_addr()) {
+            // `max` is initialized, so the gaps all end below it.
+            self.fill_range(min..max, value);
+        }
+    }
+
+    /// Merge segments separated by gaps of up to `max_gap` bytes, initializing the gaps
+    /// with `fill`, e.g. to emit fewer records.
+    pub fn coalesce(&mut self, max_gap: Addr, fill: u8) {
+        let gaps: Vec<(Addr, Addr)> = self.gaps().filter(|(_, len)| *len <= max_gap).collect();
+        for (start, len) in gaps {
+            self.write_slice(start, &alloc::vec![fill; len as usize]);
+        }
+    }
+
+    /// Initialize every uninitialized byte in `range` with `value`.
+    pub fn fill_range(&mut self, range: Range<Addr>, value: u8) {
+        let initialized: Vec<Range<Addr>> = self.initialized_in(range.clone()).collect();
+        let mut hole_start = range.start;
+        for next in initialized
+            .into_iter()
+            .chain(core::iter::once(range.end..range.end))
+        {
+            if next.start > hole_start {
+                let len = (next.start - hole_start) as usize;
+                self.write_slice(hole_start, &alloc::vec![value; len]);
+            }
+            hole_start = next.end;
+        }
+    }
+
+    /// Store `data` at `addr`, e.g. to patch a board ID into a loaded image.
+    ///
+    /// Segments are split and merged automatically: overlapping data is overwritten
+    /// and segments touching the written range are joined into one.
+    /// Bytes that would go past `Addr::MAX` are dropped.
+    pub fn write_slice(&mut self, addr: Addr, data: &[u8]) {
+        let data = clip_to_addr_space(addr, data);
+        if data.is_empty() {
+            return;
+        }
+        // Saturates when the data ends at `Addr::MAX`.
+        let end = addr.saturating_add(data.len() as Addr);
+
+        // Fast path: appending to the end of a segment, the common case when loading.
+        if let Some((&start, bytes)) = self.segments.range(..=addr).next_back()
+            && start.checked_add(bytes.len() as Addr) == Some(addr)
+            && self.segments.range(addr..=end).next().is_none()
+            && let Some(bytes) = self.segments.get_mut(&start)
+        {
+            bytes.extend_from_slice(data);
+            return;
+        }
+
+        // Segments overlapping or touching [addr, end), highest first.
+        let mut touching: Vec<Addr> = self
+            .segments
+            .range(..=end)
+            .rev()
+            .take_while(|(start, bytes)| start.saturating_add(bytes.len() as Addr) >= addr)
+            .map(|(start, _)| *start)
+            .collect();
+
+        let Some(lowest) = touching.pop() else {
+            self.segments.insert(addr, data.to_vec());
+            return;
+        };
+
+        let mut merged = self.segments.remove(&lowest).unwrap_or_default();
+        let start = lowest.min(addr);
+        if addr < lowest {
+            // The prefix is covered by `data` below.
+            let mut prefixed = alloc::vec![0; (lowest - addr) as usize];
+            prefixed.append(&mut merged);
+            merged = prefixed;
+        }
+
+        for other in touching.into_iter().rev() {
+            let bytes = self.segments.remove(&other).unwrap_or_default();
+            copy_at(&mut merged, (other - start) as usize, &bytes);
+        }
+        copy_at(&mut merged, (addr - start) as usize, data);
+
+        self.segments.insert(start, merged);
+    }
+}
+
+/// Returns the part of `data` that fits into the address space at `addr`.
+fn clip_to_addr_space(addr: Addr, data: &[u8]) -> &[u8] {
+    let room = usize::try_from(Addr::MAX - addr).map_or(data.len(), |room| room.saturating_add(1));
+    &data[..data.len().min(room)]
+}
+
+/// Append `range` to sorted `ranges`, joining it with the last one if they touch.
+/// An empty range marks a conflict at `Addr::MAX` and is kept.
+fn push_range(ranges: &mut Vec<Range<Addr>>, range: Range<Addr>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start && !range.is_empty() => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+/// Copy `data` into `dest` at `offset`, growing `dest` as needed.
+fn copy_at(dest: &mut Vec<u8>, offset: usize, data: &[u8]) {
+    let end = offset + data.len();
+    if dest.len() < end {
+        dest.resize(end, 0);
+    }
+    dest[offset..end].copy_from_slice(data);
+}
+
+/// Stack of maps where upper layers shadow lower ones, see [`MemoryMap::overlay`].
+///
+/// Writes go to a layer owned by the overlay, the stacked maps stay untouched.
+#[derive(Debug, Clone)]
+pub struct Overlay<'m> {
+    /// Base map first.
+    layers: Vec<&'m MemoryMap>,
+    /// Topmost layer with data written through the overlay.
+    patch: MemoryMap,
+}
+
+impl<'m> Overlay<'m> {
+    /// Create an overlay with no layers on top of `base`.
+    pub fn new(base: &'m MemoryMap) -> Self {
+        Overlay {
+            layers: alloc::vec![base],
+            patch: MemoryMap::new(),
+        }
+    }
+
+    /// Put `layer` on top of the stacked maps, below the data written through the overlay.
+    pub fn push_layer(&mut self, layer: &'m MemoryMap) {
+        self.layers.push(layer);
+    }
+
+    /// Returns byte at `addr` from the topmost layer that has it.
+    pub fn get(&self, addr: Addr) -> Option<u8> {
+        self.patch
+            .get(addr)
+            .or_else(|| self.layers.iter().rev().find_map(|layer| layer.get(addr)))
+    }
+
+    /// Store `value` at `addr` on top of all layers.
+    pub fn write_u8(&mut self, addr: Addr, value: u8) {
+        self.patch.write_u8(addr, value);
+    }
+
+    /// Store `data` starting at `addr` on top of all layers.
+    pub fn write_slice(&mut self, addr: Addr, data: &[u8]) {
+        self.patch.write_slice(addr, data);
+    }
+
+    /// Returns a new map with the combined data of all layers.
+    pub fn flatten(&self) -> MemoryMap {
+        let mut map = self.layers[0].clone();
+        for layer in self.layers[1..].iter().copied().chain([&self.patch]) {
+            for (addr, bytes) in layer.segments() {
+                map.write_slice(addr, bytes);
+            }
+        }
+        map
+    }
+}
+
+/// Immutable `Send + Sync` snapshot of a [`MemoryMap`], see [`MemoryMap::freeze`].
+///
+/// Cloning is cheap, all clones share the same data.
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrozenMap {
+    inner: Arc<FrozenSegments>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Debug, PartialEq)]
+struct FrozenSegments {
+    /// Segment start addresses, sorted.
+    starts: Box<[Addr]>,
+    /// Offset of every segment in `data`, followed by the total length.
+    offsets: Box<[usize]>,
+    data: Box<[u8]>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl FrozenMap {
+    /// Returns byte at `addr`, `None` if it is not initialized.
+    pub fn get(&self, addr: Addr) -> Option<u8> {
+        self.read_slice(addr, 1).map(|bytes| bytes[0])
+    }
+
+    /// Returns `len` bytes starting at `addr`, `None` if any of them is not initialized.
+    pub fn read_slice(&self, addr: Addr, len: usize) -> Option<&[u8]> {
+        let inner = &*self.inner;
+        let index = inner.starts.partition_point(|start| *start <= addr);
+        let index = index.checked_sub(1)?;
+        let offset = usize::try_from(addr - inner.starts[index]).ok()?;
+        let bytes = &inner.data[inner.offsets[index]..inner.offsets[index + 1]];
+        bytes.get(offset..offset.checked_add(len)?)
+    }
+
+    /// Iterate over segments as `(start address, bytes)` in address order.
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = (Addr, &[u8])> {
+        let inner = &*self.inner;
+        inner
+            .starts
+            .iter()
+            .zip(inner.offsets.windows(2))
+            .map(|(start, range)| (*start, &inner.data[range[0]..range[1]]))
+    }
+
+    /// Returns total number of initialized bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.inner.data.len() as u64
+    }
+}
+
+/// Serialized as a sequence of `{"addr": "0x81000000", "data": "09A0F322"}` segments,
+/// with the address and the data as hex strings.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt::Write;
+
+    use serde::de::Error;
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::MemoryMap;
+    use crate::Addr;
+
+    #[derive(Serialize, Deserialize)]
+    struct Segment {
+        addr: String,
+        data: String,
+    }
+
+    impl Serialize for MemoryMap {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.segments.len()))?;
+            for (addr, bytes) in self.segments() {
+                let mut data = String::with_capacity(2 * bytes.len());
+                for byte in bytes {
+                    let _ = write!(data, "{byte:02X}");
+                }
+                seq.serialize_element(&Segment {
+                    addr: format!("{addr:#010X}"),
+                    data,
+                })?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for MemoryMap {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut map = MemoryMap::new();
+            for segment in Vec::<Segment>::deserialize(deserializer)? {
+                let addr = segment.addr.trim_start_matches("0x");
+                let addr = Addr::from_str_radix(addr, 16)
+                    .map_err(|_| D::Error::custom("invalid segment address"))?;
+                if !segment.data.is_ascii() || !segment.data.len().is_multiple_of(2) {
+                    return Err(D::Error::custom("invalid segment data"));
+                }
+                let bytes = (0..segment.data.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&segment.data[i..i + 2], 16))
+                    .collect::<Result<Vec<u8>, _>>()
+                    .map_err(|_| D::Error::custom("invalid segment data"))?;
+                map.write_slice(addr, &bytes);
+            }
+            Ok(map)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_reader() {
+        let map = MemoryMap::from_reader(Reader::new(crate::TEXT_STR)).unwrap();
+        assert_eq!(map.segments.len(), 1);
+        assert_eq!(map.get(0x81000000), Some(0x09));
+        assert_eq!(map.get(0x81000081), Some(0x71));
+        assert_eq!(map.get(0x80FFFFFF), None);
+        assert_eq!(map.bytes().count(), 160);
+
+        let options = crate::ReaderOptions {
+            group: true,
+            ..Default::default()
+        };
+        let grouped =
+            MemoryMap::from_reader(Reader::new_with_options(crate::TEXT_STR, options)).unwrap();
+        assert_eq!(grouped, map);
+
+        let options = crate::ReaderOptions {
+            data_lines: true,
+            ..Default::default()
+        };
+        let lines =
+            MemoryMap::from_reader(Reader::new_with_options(crate::TEXT_STR, options)).unwrap();
+        assert_eq!(lines, map);
+    }
+
+    #[test]
+    fn test_typed_reads() {
+        let map = MemoryMap::from_reader(Reader::new(crate::TEXT_STR)).unwrap();
+        assert_eq!(map.read_u8(0x81000001), Some(0xA0));
+        assert_eq!(map.read_u16(0x81000000, Endian::Little), Some(0xA009));
+        assert_eq!(map.read_u16(0x81000000, Endian::Big), Some(0x09A0));
+        assert_eq!(map.read_u32(0x81000000, Endian::Little), Some(0x22F3A009));
+        assert_eq!(
+            map.read_u64(0x81000000, Endian::Big),
+            Some(0x09A0F32220346384)
+        );
+        assert_eq!(map.read_u32(0x8100009E, Endian::Little), None);
+        assert_eq!(map.read_u32(0x80FFFFFF, Endian::Little), None);
+        assert_eq!(map.read_slice(0x8100009E, 2), Some(&[0x03, 0x27][..]));
+    }
+
+    #[test]
+    fn test_patch() {
+        let mut map = MemoryMap::from_reader(Reader::new("@10 01 02 03 04 @20 05")).unwrap();
+        map.write_slice(0x11, &[0xAA, 0xBB]);
+        map.write_u8(0x1F, 0xCC);
+        assert_eq!(map.read_slice(0x10, 4), Some(&[0x01, 0xAA, 0xBB, 0x04][..]));
+        assert_eq!(map.read_slice(0x1F, 2), Some(&[0xCC, 0x05][..]));
+        assert_eq!(map.segments.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_readers() {
+        let readers = || {
+            [
+                Reader::new("@10 01 02 03 04"),
+                Reader::new("@12 AA BB CC @20 DD"),
+            ]
+        };
+
+        assert_eq!(
+            MemoryMap::merge_readers(readers(), ConflictPolicy::Error),
+            Err(MergeError::Conflict { addr: 0x12 })
+        );
+
+        let (map, conflicts) =
+            MemoryMap::merge_readers(readers(), ConflictPolicy::FirstWins).unwrap();
+        assert_eq!(conflicts, alloc::vec![0x12..0x14]);
+        assert_eq!(
+            map.read_slice(0x10, 5),
+            Some(&[0x01, 0x02, 0x03, 0x04, 0xCC][..])
+        );
+        assert_eq!(map.get(0x20), Some(0xDD));
+
+        let (map, conflicts) =
+            MemoryMap::merge_readers(readers(), ConflictPolicy::LastWins).unwrap();
+        assert_eq!(conflicts, alloc::vec![0x12..0x14]);
+        assert_eq!(
+            map.read_slice(0x10, 5),
+            Some(&[0x01, 0x02, 0xAA, 0xBB, 0xCC][..])
+        );
+    }
+
+    #[test]
+    fn test_load_with_policy() {
+        let text = "@10 01 02 03 @11 AA BB @20 CC";
+
+        let mut map = MemoryMap::new();
+        assert_eq!(
+            map.load_with_policy(Reader::new(text), ConflictPolicy::Error),
+            Err(MergeError::Conflict { addr: 0x11 })
+        );
+
+        let mut map = MemoryMap::new();
+        let overwritten = map
+            .load_with_policy(Reader::new(text), ConflictPolicy::LastWins)
+            .unwrap();
+        assert_eq!(overwritten, alloc::vec![0x11..0x13]);
+        assert_eq!(map.read_slice(0x10, 3), Some(&[0x01, 0xAA, 0xBB][..]));
+
+        let mut map = MemoryMap::new();
+        map.load_with_policy(Reader::new(text), ConflictPolicy::FirstWins)
+            .unwrap();
+        assert_eq!(map.read_slice(0x10, 3), Some(&[0x01, 0x02, 0x03][..]));
+        assert_eq!(map.get(0x20), Some(0xCC));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let map = MemoryMap::from_reader(Reader::new("@10 01 02 @20 03 @30 04 05")).unwrap();
+        assert_eq!(map.min_addr(), Some(0x10));
+        assert_eq!(map.max_addr(), Some(0x31));
+        assert_eq!(map.total_bytes(), 5);
+        assert_eq!(map.segment_count(), 3);
+
+        let map = MemoryMap::new();
+        assert_eq!(map.min_addr(), None);
+        assert_eq!(map.max_addr(), None);
+        assert_eq!(map.total_bytes(), 0);
+    }
+
+    #[test]
+    fn test_gaps() {
+        let map = MemoryMap::from_reader(Reader::new("@10 01 02 @20 03 @30 04 05")).unwrap();
+        assert!(map.gaps().eq([(0x12, 0x0E), (0x21, 0x0F)]));
+        assert_eq!(MemoryMap::new().gaps().count(), 0);
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        let mut map = MemoryMap::from_reader(Reader::new("@10 01 02 @14 03 @18 04")).unwrap();
+        map.fill_range(0x0E..0x16, 0x00);
+        assert_eq!(map.segment_count(), 2);
+        assert_eq!(
+            map.read_slice(0x0E, 8),
+            Some(&[0x00, 0x00, 0x01, 0x02, 0x00, 0x00, 0x03, 0x00][..])
+        );
+        map.fill_gaps(0xFF);
+        assert_eq!(map.segment_count(), 1);
+        assert_eq!(map.read_slice(0x16, 3), Some(&[0xFF, 0xFF, 0x04][..]));
+        assert_eq!(map.max_addr(), Some(0x18));
+
+        let mut map = MemoryMap::from_reader(Reader::new("@10 01 @13 02")).unwrap();
+        map.rebase(Addr::MAX - 3).unwrap();
+        map.fill_gaps(0xFF);
+        assert_eq!(map.segment_count(), 1);
+        assert_eq!(
+            map.read_slice(Addr::MAX - 3, 4),
+            Some(&[0x01, 0xFF, 0xFF, 0x02][..])
+        );
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let mut map = MemoryMap::new();
+        map.write_slice(0x10, &[1, 2]);
+        map.write_slice(0x14, &[3]);
+        map.write_slice(0x20, &[4]);
+        map.coalesce(2, 0xFF);
+        assert_eq!(map.segment_count(), 2);
+        assert_eq!(map.read_slice(0x10, 5), Some(&[1, 2, 0xFF, 0xFF, 3][..]));
+        map.coalesce(Addr::MAX, 0);
+        assert_eq!(map.segment_count(), 1);
+        assert_eq!(map.get(0x1F), Some(0));
+    }
+
+    #[test]
+    fn test_words() {
+        let mut map = MemoryMap::new();
+        map.write_slice(0x13, &[0x01, 0x02]);
+        map.write_slice(0x19, &[0x03]);
+        assert_eq!(
+            map.words::<u32>(Endian::Little, 0xEE).collect::<Vec<_>>(),
+            [(0x10, 0x01EEEEEE), (0x14, 0xEEEEEE02), (0x18, 0xEEEE03EE)]
+        );
+        assert_eq!(
+            map.words::<u16>(Endian::Big, 0).collect::<Vec<_>>(),
+            [(0x12, 0x0001), (0x14, 0x0200), (0x16, 0), (0x18, 0x0003)]
+        );
+        assert_eq!(MemoryMap::new().words::<u64>(Endian::Little, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_apply_patches() {
+        let mut map = MemoryMap::new();
+        map.write_slice(0x10, &[0; 4]);
+        let original = map.clone();
+        assert_eq!(
+            map.apply_patches(&[(0x10, &[1]), (0x13, &[2, 3])], true),
+            Err(MapError::Uninitialized { addr: 0x14 })
+        );
+        assert_eq!(
+            map.apply_patches(&[(0x10, &[1]), (Addr::MAX, &[2, 3])], false),
+            Err(MapError::AddressOverflow)
+        );
+        assert_eq!(map, original);
+
+        map.apply_patches(&[(0x10, &[1]), (0x12, &[2, 3])], true)
+            .unwrap();
+        assert_eq!(map.read_slice(0x10, 4), Some(&[1, 0, 2, 3][..]));
+        map.apply_patches(&[(0x14, &[4])], false).unwrap();
+        assert_eq!(map.total_bytes(), 5);
+    }
+
+    #[test]
+    fn test_extract() {
+        let map = MemoryMap::from_reader(Reader::new("@10 01 02 03 @20 04 05 @30 06")).unwrap();
+        let window = map.extract(0x11..0x21);
+        assert!(
+            window
+                .bytes()
+                .eq([(0x11, 0x02), (0x12, 0x03), (0x20, 0x04)])
+        );
+        assert!(map.extract(0x13..0x20).is_empty());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 0x20..0x10;
+        assert!(map.extract(reversed.clone()).is_empty());
+        assert!(map.to_vec_range(reversed.clone(), 0).is_empty());
+        let mut filled = map.clone();
+        filled.fill_range(reversed, 0);
+        assert_eq!(filled, map);
+    }
+
+    #[test]
+    fn test_rebase() {
+        let mut map = MemoryMap::from_reader(Reader::new("@81000000 01 02 @81000010 03")).unwrap();
+        map.rebase(0).unwrap();
+        assert!(map.bytes().eq([(0x00, 0x01), (0x01, 0x02), (0x10, 0x03)]));
+        map.offset(0x100).unwrap();
+        assert_eq!(map.min_addr(), Some(0x100));
+        assert_eq!(map.offset(-0x101), Err(MapError::AddressOverflow));
+        assert_eq!(map.rebase(Addr::MAX - 0x0F), Err(MapError::AddressOverflow));
+        map.rebase(Addr::MAX - 0x10).unwrap();
+        assert_eq!(map.max_addr(), Some(Addr::MAX));
+    }
+
+    #[test]
+    fn test_to_vec() {
+        let map = MemoryMap::from_reader(Reader::new("@10 01 02 @14 03")).unwrap();
+        assert_eq!(map.to_vec(0xFF), [0x01, 0x02, 0xFF, 0xFF, 0x03]);
+        assert_eq!(map.to_vec_range(0x0F..0x13, 0x00), [0x00, 0x01, 0x02, 0x00]);
+        assert!(MemoryMap::new().to_vec(0).is_empty());
+
+        let mut map = MemoryMap::from_reader(Reader::new("@10 01 @12 02")).unwrap();
+        map.rebase(Addr::MAX - 2).unwrap();
+        assert_eq!(map.to_vec(0xFF), [0x01, 0xFF, 0x02]);
+    }
+
+    #[test]
+    fn test_segments() {
+        let map = MemoryMap::from_reader(Reader::new("@10 01 02 @14 03")).unwrap();
+        assert!(
+            map.segments()
+                .eq([(0x10, &[0x01, 0x02][..]), (0x14, &[0x03][..])])
+        );
+        assert_eq!(map.segments().next_back(), Some((0x14, &[0x03][..])));
+    }
+
+    #[test]
+    fn test_diff() {
+        let left = MemoryMap::from_reader(Reader::new("@10 01 02 03 @20 04")).unwrap();
+        let right = MemoryMap::from_reader(Reader::new("@11 02 AA 05 @30 06")).unwrap();
+        assert!(left.diff(&right).eq([
+            Difference::OnlyInLeft {
+                addr: 0x10,
+                value: 0x01
+            },
+            Difference::Changed {
+                addr: 0x12,
+                old: 0x03,
+                new: 0xAA
+            },
+            Difference::OnlyInRight {
+                addr: 0x13,
+                value: 0x05
+            },
+            Difference::OnlyInLeft {
+                addr: 0x20,
+                value: 0x04
+            },
+            Difference::OnlyInRight {
+                addr: 0x30,
+                value: 0x06
+            },
+        ]));
+        assert_eq!(left.diff(&left).count(), 0);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let map =
+            MemoryMap::from_reader(Reader::new("@10 31 32 33 34 @20 35 36 37 38 39")).unwrap();
+        assert_eq!(
+            map.checksum(ChecksumAlgorithm::Crc32).as_bytes(),
+            [0xCB, 0xF4, 0x39, 0x26]
+        );
+        assert_eq!(
+            map.checksum(ChecksumAlgorithm::Crc16).as_bytes(),
+            [0x29, 0xB1]
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let mut map =
+            MemoryMap::from_reader(Reader::new("@10 DE AD BE EF AD AD @20 DE AD")).unwrap();
+        map.write_slice(0x16, &[0xAD]);
+        assert!(map.find(&[0xDE, 0xAD, 0xBE, 0xEF]).eq([0x10]));
+        assert!(map.find(&[0xAD, 0xAD]).eq([0x14, 0x15]));
+        assert!(map.find(&[0xDE, 0xAD]).eq([0x10, 0x20]));
+        assert_eq!(map.find(&[0xAD, 0xDE]).count(), 0);
+        assert_eq!(map.find(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut map = MemoryMap::new();
+        map.write_slice(0x100, &[0xFF; 8]);
+        map.write_slice(0x200, &(0..=255).collect::<Vec<u8>>());
+        let stats = map.stats(128);
+        assert_eq!(stats.histogram[0xFF], 9);
+        assert_eq!(stats.histogram[0x00], 1);
+        assert_eq!(stats.most_common(), Some(0xFF));
+        assert_eq!(
+            stats
+                .window_entropy
+                .iter()
+                .map(|(addr, _)| *addr)
+                .collect::<Vec<_>>(),
+            [0x100, 0x200, 0x280]
+        );
+        assert!(stats.window_entropy[0].1.abs() < 1e-9);
+        assert!((stats.window_entropy[1].1 - 7.0).abs() < 1e-9);
+        assert!(stats.entropy() > 7.0 && stats.entropy() < 8.0);
+        assert!((log2(1000.0) - 9.965784284662087).abs() < 1e-12);
+        assert_eq!(MemoryMap::new().stats(16).most_common(), None);
+    }
+
+    #[test]
+    fn test_regions() {
+        let mut map = MemoryMap::from_reader(Reader::new(crate::TEXT_STR)).unwrap();
+        map.add_region(".data", 0x81000080..0x81000100).unwrap();
+        map.add_region(".text", 0x81000000..0x81000080).unwrap();
+        assert_eq!(
+            map.add_region(".bss", 0x810000F0..0x81000200),
+            Err(MapError::RegionOverlap { addr: 0x810000F0 })
+        );
+        assert_eq!(
+            map.regions().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            [".text", ".data"]
+        );
+        assert_eq!(map.region_of(0x81000080).unwrap().name, ".data");
+        assert_eq!(map.region_of(0x81000100), None);
+        assert_eq!(
+            map.region_sizes()
+                .map(|(r, size)| (r.name.as_str(), size))
+                .collect::<Vec<_>>(),
+            [(".text", 0x80), (".data", 0x20)]
+        );
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn test_freeze() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let map = MemoryMap::from_reader(Reader::new(crate::TEXT_STR)).unwrap();
+        let mut sparse = map.clone();
+        sparse.write_u8(0x90000000, 0xAA);
+        let frozen = sparse.freeze();
+        let shared = frozen.clone();
+        assert_send_sync(&shared);
+        assert_eq!(shared.total_bytes(), map.total_bytes() + 1);
+        assert_eq!(shared.get(0x81000000), Some(0x09));
+        assert_eq!(shared.get(0x90000000), Some(0xAA));
+        assert_eq!(shared.get(0x90000001), None);
+        assert_eq!(shared.read_slice(0x8100009F, 2), None);
+        assert_eq!(
+            shared.segments().map(|(addr, _)| addr).collect::<Vec<_>>(),
+            [0x81000000, 0x90000000]
+        );
+        assert_eq!(MemoryMap::new().freeze().get(0), None);
+    }
+
+    #[test]
+    fn test_overlay() {
+        let base = MemoryMap::from_reader(Reader::new(crate::TEXT_STR)).unwrap();
+        let mut board = MemoryMap::new();
+        board.write_slice(0x81000001, &[0xB0, 0xB1]);
+        let mut overlay = base.overlay();
+        overlay.push_layer(&board);
+        overlay.write_slice(0x81000002, &[0xC2]);
+        overlay.write_u8(0x90000000, 0xC3);
+        assert_eq!(overlay.get(0x81000000), Some(0x09));
+        assert_eq!(overlay.get(0x81000001), Some(0xB0));
+        assert_eq!(overlay.get(0x81000002), Some(0xC2));
+        assert_eq!(base.get(0x81000002), Some(0xF3));
+
+        let flat = overlay.flatten();
+        assert_eq!(
+            flat.read_slice(0x81000000, 4),
+            Some(&[0x09, 0xB0, 0xC2, 0x22][..])
+        );
+        assert_eq!(flat.get(0x90000000), Some(0xC3));
+        assert_eq!(flat.total_bytes(), base.total_bytes() + 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "std"))]
+    fn test_serde() {
+        let mut map = MemoryMap::new();
+        map.write_slice(0x81000000, &[0x09, 0xA0, 0xF3]);
+        map.write_slice(0x81000010, &[0x22]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"addr":"0x81000000","data":"09A0F3"},{"addr":"0x81000010","data":"22"}]"#
+        );
+        assert_eq!(serde_json::from_str::<MemoryMap>(&json).unwrap(), map);
+        assert!(serde_json::from_str::<MemoryMap>(r#"[{"addr":"0x10","data":"0"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_write_slice_merges_segments() {
+        let mut map = MemoryMap::new();
+        map.write_slice(0x10, &[1, 2]);
+        map.write_slice(0x20, &[3]);
+        map.write_slice(0x0E, &[4, 5]);
+        assert_eq!(map.segments.len(), 2);
+        map.write_slice(0x11, &[6; 15]);
+        assert_eq!(map.segments.len(), 1);
+        assert!(
+            map.bytes()
+                .map(|(_, value)| value)
+                .eq([4, 5, 1, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 3])
+        );
+        map.write_slice(0x0D, &[7]);
+        assert_eq!(map.get(0x0D), Some(7));
+        assert_eq!(map.segments.keys().copied().collect::<Vec<_>>(), [0x0D]);
+    }
+
+    #[test]
+    fn test_write_slice_at_top() {
+        let mut map = MemoryMap::new();
+        map.write_u8(Addr::MAX, 1);
+        map.write_slice(Addr::MAX - 2, &[2, 3]);
+        map.write_slice(Addr::MAX - 1, &[4, 5, 6]);
+        assert!(
+            map.bytes()
+                .eq([(Addr::MAX - 2, 2), (Addr::MAX - 1, 4), (Addr::MAX, 5)])
+        );
+
+        let conflicts = map
+            .write_slice_with_policy(Addr::MAX - 3, &[7, 8, 9, 10], ConflictPolicy::FirstWins)
+            .unwrap();
+        assert_eq!(conflicts, [Addr::MAX - 2..Addr::MAX, Addr::MAX..Addr::MAX]);
+        assert!(map.bytes().map(|(_, value)| value).eq([7, 2, 4, 5]));
+        assert_eq!(
+            map.write_slice_with_policy(Addr::MAX, &[0], ConflictPolicy::Error),
+            Err(MergeError::Conflict { addr: Addr::MAX })
+        );
+    }
+}
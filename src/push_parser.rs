@@ -0,0 +1,410 @@
+//! Resumable parser fed with input in chunks.
+//!
+//! # Streaming example:
+//!
+//! ```ignore
+//! let mut parser = crate::PushParser::new();
+//! while let Some(chunk) = uart.receive() {
+//!     parser.feed(chunk, |record| std::println!("{}", record.unwrap()));
+//! }
+//! parser.finish(|record| std::println!("{}", record.unwrap()));
+//! ```
+
+use crate::tokenizer::{Token, is_line_comment};
+use crate::{
+    Addr, DataType, ParseError, ReaderOptions, Record, check_budget, check_order,
+    detect_token_bytes, group_new_data, parse_token, skip_outside_window, split_data,
+};
+
+/// Longest token kept across chunk boundaries, enough for 128 binary digits with
+/// underscores between bytes. Longer tokens are reported as
+/// [`crate::ReaderError::BadNumberConversion`] unless they are comments, whose text is cut,
+/// so a [`Record::Symbol`] with a longer comment is read as [`Record::Comment`].
+pub const MAX_TOKEN_LEN: usize = 256;
+
+/// Push parser producing the same records as [`crate::Reader`] from input fed in chunks
+/// of any size, e.g. received over a socket or UART, without `alloc`.
+pub struct PushParser<'a> {
+    /// Configuration options.
+    options: ReaderOptions<'a>,
+    /// Token being read, possibly started in a previous chunk.
+    partial: [u8; MAX_TOKEN_LEN],
+    partial_len: usize,
+    /// The partial token did not fit into `partial`.
+    truncated: bool,
+    /// Line, column and offset of the partial token.
+    partial_start: (usize, usize, usize),
+    /// Line, column and offset of the open block comment
+    /// and whether the last byte in it was `*`.
+    comment: Option<(usize, usize, usize, bool)>,
+    /// Offset of the next input byte.
+    offset: usize,
+    /// Current line and offset of its start.
+    line: usize,
+    line_start: usize,
+    /// Current address.
+    current_addr: Addr,
+    /// Number of data bytes read.
+    data_bytes: usize,
+    /// Group being accumulated and the line of its first byte.
+    pending: Option<(Record<'static>, usize)>,
+    /// Reading completed.
+    finished: bool,
+}
+
+impl Default for PushParser<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> PushParser<'a> {
+    /// Create a new parser with the specified options.
+    pub fn new_with_options(options: ReaderOptions<'a>) -> Self {
+        PushParser {
+            options,
+            partial: [0; MAX_TOKEN_LEN],
+            partial_len: 0,
+            truncated: false,
+            partial_start: (0, 0, 0),
+            comment: None,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+            current_addr: 0,
+            data_bytes: 0,
+            pending: None,
+            finished: false,
+        }
+    }
+
+    /// Create a new parser with default options.
+    pub fn new() -> Self {
+        PushParser::new_with_options(Default::default())
+    }
+
+    /// Parse the next `chunk` of input and call `callback` for every complete record.
+    ///
+    /// A token at the end of the chunk is kept until the next chunk or [`PushParser::finish`].
+    pub fn feed<F>(&mut self, chunk: &[u8], mut callback: F)
+    where
+        F: FnMut(Result<Record<'_>, ParseError<'_>>),
+    {
+        for byte in chunk {
+            let offset = self.offset;
+            self.offset += 1;
+            if let Some(start) = &mut self.comment {
+                if start.3 && *byte == b'/' {
+                    self.comment = None;
+                } else {
+                    start.3 = *byte == b'*';
+                }
+            } else if byte.is_ascii_whitespace() && (*byte == b'\n' || !self.in_line_comment()) {
+                self.flush_partial(&mut callback);
+            } else if *byte == b'*'
+                && !self.truncated
+                && self.partial[..self.partial_len].ends_with(b"/")
+                && !self.in_line_comment()
+            {
+                // Block comment ends the token before it.
+                self.partial_len -= 1;
+                let column = offset - self.line_start;
+                self.flush_partial(&mut callback);
+                self.comment = Some((self.line, column, offset - 1, false));
+            } else {
+                if self.partial_len == 0 {
+                    self.partial_start = (self.line, offset - self.line_start + 1, offset);
+                }
+                self.append_partial(core::slice::from_ref(byte));
+                if let Some(len) = self.delimiter_suffix() {
+                    self.partial_len -= len;
+                    self.flush_partial(&mut callback);
+                }
+            }
+            if *byte == b'\n' {
+                self.line += 1;
+                self.line_start = offset + 1;
+            }
+        }
+    }
+
+    /// Returns number of input bytes fed so far, e.g. for a progress bar.
+    pub fn consumed_bytes(&self) -> usize {
+        self.offset
+    }
+
+    /// Parse the rest of the input and call `callback` for the remaining records.
+    pub fn finish<F>(mut self, mut callback: F)
+    where
+        F: FnMut(Result<Record<'_>, ParseError<'_>>),
+    {
+        self.flush_partial(&mut callback);
+        if let Some((line, column, offset, _)) = self.comment {
+            self.handle(
+                Token::new(b"/*", offset, line, column),
+                false,
+                &mut callback,
+            );
+        }
+        self.flush_group(&mut callback);
+        if self.options.end_of_file && !self.finished {
+            callback(Ok(Record::EndOfFile));
+        }
+    }
+
+    fn append_partial(&mut self, bytes: &[u8]) {
+        let len = bytes.len().min(MAX_TOKEN_LEN - self.partial_len);
+        self.partial[self.partial_len..self.partial_len + len].copy_from_slice(&bytes[..len]);
+        self.partial_len += len;
+        self.truncated |= len < bytes.len();
+    }
+
+    /// Returns length of [`ReaderOptions::delimiters`] separator ending the partial token.
+    fn delimiter_suffix(&self) -> Option<usize> {
+        let partial = &self.partial[..self.partial_len];
+        if self.truncated || self.in_line_comment() {
+            return None;
+        }
+        self.options.delimiters.iter().find_map(|delimiter| {
+            let mut buffer = [0; 4];
+            let encoded = delimiter.encode_utf8(&mut buffer).as_bytes();
+            partial.ends_with(encoded).then_some(encoded.len())
+        })
+    }
+
+    /// Returns `true` if the partial token is a line comment.
+    fn in_line_comment(&self) -> bool {
+        is_line_comment(
+            &self.partial[..self.partial_len],
+            self.options.comment_prefixes,
+        )
+    }
+
+    fn flush_partial<F>(&mut self, callback: &mut F)
+    where
+        F: FnMut(Result<Record<'_>, ParseError<'_>>),
+    {
+        if self.partial_len == 0 {
+            return;
+        }
+        let partial = self.partial;
+        let (line, column, offset) = self.partial_start;
+        let mut text = &partial[..self.partial_len];
+        if self.in_line_comment() {
+            text = text.trim_ascii_end();
+        }
+        let token = Token::new(text, offset, line, column);
+        let truncated = self.truncated;
+        self.partial_len = 0;
+        self.truncated = false;
+        self.handle(token, truncated, callback);
+    }
+
+    fn flush_group<F>(&mut self, callback: &mut F)
+    where
+        F: FnMut(Result<Record<'_>, ParseError<'_>>),
+    {
+        if let Some((record, _)) = self.pending.take() {
+            callback(Ok(record));
+        }
+    }
+
+    /// Process one complete token the way [`crate::Reader`] does.
+    fn handle<F>(&mut self, token: Token, truncated: bool, callback: &mut F)
+    where
+        F: FnMut(Result<Record<'_>, ParseError<'_>>),
+    {
+        if self.finished {
+            return;
+        }
+        detect_token_bytes(&mut self.options, token);
+        let mut result = parse_token(token, self.current_addr, &self.options);
+        if let Some(len) = skip_outside_window(&mut result, &self.options) {
+            self.current_addr += len as Addr;
+            return;
+        }
+        check_budget(&mut result, &self.options, &mut self.data_bytes);
+        check_order(&mut result, &self.options, self.current_addr);
+        if truncated && !matches!(result, Ok(Record::Comment(_))) {
+            result = Err(crate::ReaderError::BadNumberConversion);
+        }
+
+        if self.options.group
+            && let Ok(Record::Data {
+                addr,
+                value: DataType::U8(byte),
+            }) = result
+        {
+            let group_bytes = self.options.group_bytes.clamp(1, 16);
+            if let Some((Record::Data { addr: _, value }, line)) = &mut self.pending
+                && split_data(*value).1 < group_bytes
+                && !(self.options.align_groups
+                    && self.current_addr.is_multiple_of(group_bytes as Addr))
+                && !(self.options.group_within_line && *line != token.line)
+            {
+                *value = group_new_data(*value, byte);
+            } else {
+                self.flush_group(callback);
+                self.pending = Some((
+                    Record::Data {
+                        addr,
+                        value: DataType::U8(byte),
+                    },
+                    token.line,
+                ));
+            }
+            self.current_addr += 1;
+            return;
+        }
+
+        self.flush_group(callback);
+        match result {
+            Err(kind) => {
+                if !self.options.recover {
+                    self.finished = true;
+                }
+                callback(Err(ParseError {
+                    kind,
+                    line: token.line,
+                    column: token.column,
+                    token: token.text,
+                }));
+            }
+            Ok(record) => {
+                match record {
+                    Record::EndOfFile => self.finished = true,
+                    Record::NewAddress(addr) => self.current_addr = addr,
+                    Record::Data { addr: _, value } => {
+                        self.current_addr += split_data(value).1 as Addr
+                    }
+                    Record::Unknown { .. } => self.current_addr += 1,
+                    Record::DataLine { .. } | Record::Symbol { .. } => {}
+                    Record::Comment(_) => {}
+                }
+                callback(Ok(record));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, ReaderError};
+
+    #[test]
+    fn test_chunks_match_reader() {
+        for group in [false, true] {
+            let options = || ReaderOptions {
+                group,
+                ..Default::default()
+            };
+            let texts = [crate::TEXT_STR, "@10 09 A0 // patched  value \r\n0B //\n"];
+            for (text, chunk_size) in texts.iter().flat_map(|text| {
+                [1, 2, 3, 7, 64, 1000]
+                    .into_iter()
+                    .map(move |chunk_size| (text, chunk_size))
+            }) {
+                let mut expected = Reader::new_with_options(text, options());
+                let mut check = |record: Result<Record, ParseError<'_>>| {
+                    assert_eq!(Some(record.unwrap()), expected.next().map(Result::unwrap));
+                };
+                let mut parser = PushParser::new_with_options(options());
+                for chunk in text.as_bytes().chunks(chunk_size) {
+                    parser.feed(chunk, &mut check);
+                }
+                parser.finish(&mut check);
+                assert_eq!(expected.next(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_tokens() {
+        let options = || ReaderOptions {
+            binary: true,
+            token_bytes: 10,
+            underscores: true,
+            symbols: true,
+            ..Default::default()
+        };
+        let mut binary = [b'1'; 80 + 9];
+        for index in (8..binary.len()).step_by(9) {
+            binary[index] = b'_';
+        }
+        let symbol = concat!(
+            "// 10 <_a_very_long_symbol_name_for_a_function",
+            "_in_a_crate_with_a_long_module_path_and_generics>:\n"
+        );
+        for text in [&binary[..], symbol.as_bytes()] {
+            let text = core::str::from_utf8(text).unwrap();
+            let mut expected = Reader::new_with_options(text, options());
+            let mut count = 0;
+            let mut check = |record: Result<Record, ParseError<'_>>| {
+                assert!(matches!(
+                    record,
+                    Ok(Record::Data { .. } | Record::Symbol { .. })
+                ));
+                assert_eq!(Some(record), expected.next());
+                count += 1;
+            };
+            let mut parser = PushParser::new_with_options(options());
+            for chunk in text.as_bytes().chunks(7) {
+                parser.feed(chunk, &mut check);
+            }
+            parser.finish(&mut check);
+            assert_eq!(count, 1);
+        }
+    }
+
+    #[test]
+    fn test_delimiters() {
+        // A local array, not a `'static` one.
+        let delimiters = [',', '\u{A0}'];
+        let options = || ReaderOptions {
+            delimiters: &delimiters,
+            ..Default::default()
+        };
+        let text = "@10,0A,\u{A0}0B\u{A0},0C";
+        let mut expected = Reader::new_with_options(text, options());
+        let mut check = |record: Result<Record, ParseError<'_>>| {
+            assert_eq!(Some(record.unwrap()), expected.next().map(Result::unwrap));
+        };
+        let mut parser = PushParser::new_with_options(options());
+        for chunk in text.as_bytes().chunks(1) {
+            parser.feed(chunk, &mut check);
+        }
+        parser.finish(&mut check);
+        assert_eq!(expected.next(), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut errors = [None; 3];
+        let mut count = 0;
+        let mut parser = PushParser::new_with_options(ReaderOptions {
+            recover: true,
+            ..Default::default()
+        });
+        let mut callback = |record: Result<Record, ParseError<'_>>| {
+            if let Err(error) = record {
+                errors[count] = Some((error.kind, error.line, error.column));
+                count += 1;
+            }
+        };
+        parser.feed(b"@10\n0A X", &mut callback);
+        parser.feed(b"Y 0B\n", &mut callback);
+        parser.feed(&[b'0'; MAX_TOKEN_LEN + 6], &mut callback);
+        parser.feed(b" 0C/", &mut callback);
+        parser.feed(b"* 0D */0E /*", &mut callback);
+        parser.finish(&mut callback);
+        assert_eq!(
+            errors,
+            [
+                Some((ReaderError::BadNumberConversion, 2, 4)),
+                Some((ReaderError::BadNumberConversion, 3, 1)),
+                Some((ReaderError::UnterminatedComment, 3, MAX_TOKEN_LEN + 21)),
+            ]
+        );
+    }
+}
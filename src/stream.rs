@@ -0,0 +1,253 @@
+//! Reading records from a [`std::io::BufRead`] without loading the whole input.
+//!
+//! # Streaming example:
+//!
+//! ```ignore
+//! let file = std::io::BufReader::new(std::fs::File::open("dump.hex")?);
+//! for record in crate::StreamReader::new(file) {
+//!     std::println!("{}", record?);
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::vec::Vec;
+
+use core::error::Error;
+use core::fmt;
+
+use crate::{OwnedParseError, ParseError, PushParser, ReaderOptions, Record};
+
+/// Error of [`StreamReader`].
+#[derive(Debug)]
+pub enum StreamError {
+    /// Failed to read input.
+    Io(io::Error),
+    /// Failed to parse input.
+    Parse(OwnedParseError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "read error: {error}"),
+            StreamError::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for StreamError {}
+
+impl From<ParseError<'_>> for StreamError {
+    fn from(error: ParseError<'_>) -> Self {
+        StreamError::Parse(error.into())
+    }
+}
+
+/// Returns `record` without the comment text, which does not outlive the input buffer.
+fn detach(record: Record<'_>) -> Record<'static> {
+    match record {
+        Record::Data { addr, value } => Record::Data { addr, value },
+        Record::EndOfFile => Record::EndOfFile,
+        Record::Comment(_) => Record::Comment(""),
+        Record::NewAddress(addr) => Record::NewAddress(addr),
+        Record::Unknown { addr, value, mask } => Record::Unknown { addr, value, mask },
+        Record::DataLine { addr, hex: _ } => Record::DataLine { addr, hex: "" },
+        Record::Symbol { addr, name: _ } => Record::Symbol { addr, name: "" },
+    }
+}
+
+/// Reader of records from `impl BufRead` that keeps only one line in memory,
+/// otherwise the same as [`crate::Reader`] except that [`Record::Comment`] text
+/// and [`Record::Symbol`] names are empty.
+pub struct StreamReader<'a, R: BufRead> {
+    input: R,
+    /// `None` once the input is exhausted.
+    parser: Option<PushParser<'a>>,
+    line: Vec<u8>,
+    /// Number of input bytes read.
+    consumed: usize,
+    /// Records of the last line not returned yet.
+    records: VecDeque<Result<Record<'static>, StreamError>>,
+}
+
+impl<'a, R: BufRead> StreamReader<'a, R> {
+    /// Create a new reader with the specified options.
+    pub fn new_with_options(input: R, options: ReaderOptions<'a>) -> Self {
+        StreamReader {
+            input,
+            parser: Some(PushParser::new_with_options(options)),
+            line: Vec::new(),
+            consumed: 0,
+            records: VecDeque::new(),
+        }
+    }
+
+    /// Create a new reader with default options.
+    pub fn new(input: R) -> Self {
+        StreamReader::new_with_options(input, Default::default())
+    }
+
+    /// Returns number of input bytes read so far, e.g. for a progress bar.
+    pub fn consumed_bytes(&self) -> usize {
+        self.consumed
+    }
+}
+
+impl<R: BufRead> Iterator for StreamReader<'_, R> {
+    type Item = Result<Record<'static>, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.records.is_empty() {
+            let parser = self.parser.as_mut()?;
+            let records = &mut self.records;
+            let push = |record: Result<Record<'_>, ParseError<'_>>| {
+                records.push_back(record.map(detach).map_err(StreamError::from));
+            };
+            self.line.clear();
+            match self.input.read_until(b'\n', &mut self.line) {
+                Ok(0) => {
+                    if let Some(parser) = self.parser.take() {
+                        parser.finish(push);
+                    }
+                }
+                Ok(len) => {
+                    self.consumed += len;
+                    parser.feed(&self.line, push);
+                }
+                Err(error) => {
+                    self.parser = None;
+                    return Some(Err(StreamError::Io(error)));
+                }
+            }
+        }
+        self.records.pop_front()
+    }
+}
+
+/// Asynchronous [`StreamReader`] over `futures::AsyncBufRead`, requires the `async` feature.
+/// Comment text is empty too.
+///
+/// Records are parsed as soon as the data arrives, e.g. while downloading.
+#[cfg(feature = "async")]
+pub struct AsyncStreamReader<'a, R: futures_io::AsyncBufRead + Unpin> {
+    input: R,
+    /// `None` once the input is exhausted.
+    parser: Option<PushParser<'a>>,
+    /// Number of input bytes read.
+    consumed: usize,
+    /// Records of the last chunk not returned yet.
+    records: VecDeque<Result<Record<'static>, StreamError>>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, R: futures_io::AsyncBufRead + Unpin> AsyncStreamReader<'a, R> {
+    /// Create a new reader with the specified options.
+    pub fn new_with_options(input: R, options: ReaderOptions<'a>) -> Self {
+        AsyncStreamReader {
+            input,
+            parser: Some(PushParser::new_with_options(options)),
+            consumed: 0,
+            records: VecDeque::new(),
+        }
+    }
+
+    /// Create a new reader with default options.
+    pub fn new(input: R) -> Self {
+        AsyncStreamReader::new_with_options(input, Default::default())
+    }
+
+    /// Returns number of input bytes read so far, e.g. for a progress bar.
+    pub fn consumed_bytes(&self) -> usize {
+        self.consumed
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: futures_io::AsyncBufRead + Unpin> futures_core::Stream for AsyncStreamReader<'_, R> {
+    type Item = Result<Record<'static>, StreamError>;
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Option<Self::Item>> {
+        use core::pin::Pin;
+        use core::task::Poll;
+
+        let this = self.get_mut();
+        loop {
+            if let Some(record) = this.records.pop_front() {
+                return Poll::Ready(Some(record));
+            }
+            let Some(parser) = this.parser.as_mut() else {
+                return Poll::Ready(None);
+            };
+            let records = &mut this.records;
+            let push = |record: Result<Record<'_>, ParseError<'_>>| {
+                records.push_back(record.map(detach).map_err(StreamError::from));
+            };
+            match Pin::new(&mut this.input).poll_fill_buf(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok([])) => {
+                    if let Some(parser) = this.parser.take() {
+                        parser.finish(push);
+                    }
+                }
+                Poll::Ready(Ok(chunk)) => {
+                    let len = chunk.len();
+                    this.consumed += len;
+                    parser.feed(chunk, push);
+                    Pin::new(&mut this.input).consume(len);
+                }
+                Poll::Ready(Err(error)) => {
+                    this.parser = None;
+                    return Poll::Ready(Some(Err(StreamError::Io(error))));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream() {
+        let records = StreamReader::new(crate::TEXT_STR.as_bytes()).map(Result::unwrap);
+        assert!(records.eq(crate::Reader::new(crate::TEXT_STR).map(Result::unwrap)));
+
+        let mut reader = StreamReader::new(&b"@10\n0A\n0X\n"[..]);
+        reader.next();
+        assert_eq!(reader.consumed_bytes(), 4);
+        let mut reader = StreamReader::new(&b"@10\n0A\n0X\n"[..]);
+        let Some(Err(StreamError::Parse(error))) = reader.nth(2) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(error.kind, crate::ReaderError::BadNumberConversion);
+        assert_eq!((error.line, error.column, error.token()), (3, 1, "0X"));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_async_stream() {
+        use core::pin::Pin;
+        use core::task::{Context, Poll, Waker};
+        use futures_core::Stream;
+
+        let mut reader = AsyncStreamReader::new(crate::TEXT_STR.as_bytes());
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut expected = crate::Reader::new(crate::TEXT_STR);
+        loop {
+            match Pin::new(&mut reader).poll_next(&mut cx) {
+                Poll::Ready(Some(record)) => {
+                    assert_eq!(Some(record.unwrap()), expected.next().map(Result::unwrap))
+                }
+                Poll::Ready(None) => break,
+                Poll::Pending => unreachable!(),
+            }
+        }
+        assert_eq!(expected.next(), None);
+    }
+}
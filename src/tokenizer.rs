@@ -0,0 +1,294 @@
+//! Splitting input text into whitespace separated tokens with their positions.
+
+use core::ops::Range;
+use core::str;
+
+/// Token text with the position of its first character.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Token<'a> {
+    /// Token text, cut before the first invalid UTF-8 sequence.
+    pub text: &'a str,
+    /// Token has non-ASCII bytes.
+    pub non_ascii: bool,
+    /// Byte offset from the start of the input.
+    pub offset: usize,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column in bytes.
+    pub column: usize,
+}
+
+impl<'a> Token<'a> {
+    pub fn new(bytes: &'a [u8], offset: usize, line: usize, column: usize) -> Self {
+        let text = match str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(error) => str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default(),
+        };
+        Token {
+            text,
+            non_ascii: !bytes.is_ascii(),
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+/// Returns `true` if `bytes` start with `//` or one of `comment_prefixes`,
+/// such a token runs to the end of the line.
+pub(crate) fn is_line_comment(bytes: &[u8], comment_prefixes: &[&str]) -> bool {
+    bytes.starts_with(b"//")
+        || comment_prefixes
+            .iter()
+            .any(|prefix| !prefix.is_empty() && bytes.starts_with(prefix.as_bytes()))
+}
+
+/// Location in the input text.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column in bytes.
+    pub column: usize,
+    /// Byte offset from the start of the input.
+    pub offset: usize,
+}
+
+/// Iterator over tokens separated by ASCII whitespace and extra delimiters, like
+/// [`str::split_ascii_whitespace`] but over bytes, that keeps track of lines.
+#[derive(Debug, Clone)]
+pub(crate) struct Tokenizer<'a> {
+    bytes: &'a [u8],
+    /// Token separators in addition to ASCII whitespace.
+    delimiters: &'a [char],
+    /// Line comment markers in addition to `//`.
+    comment_prefixes: &'a [&'a str],
+    offset: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Tokenizer {
+            bytes,
+            delimiters: &[],
+            comment_prefixes: &[],
+            offset: 0,
+            line: 1,
+            line_start: 0,
+        }
+    }
+
+    /// Separate tokens with `delimiters` too.
+    pub fn with_delimiters(mut self, delimiters: &'a [char]) -> Self {
+        self.delimiters = delimiters;
+        self
+    }
+
+    /// Treat tokens starting with `comment_prefixes` as line comments too.
+    pub fn with_comment_prefixes(mut self, comment_prefixes: &'a [&'a str]) -> Self {
+        self.comment_prefixes = comment_prefixes;
+        self
+    }
+
+    /// Returns length of the separator at `offset`, 0 if there is none.
+    fn delimiter_len(&self, offset: usize) -> usize {
+        let bytes = &self.bytes[offset..];
+        if bytes.first().is_some_and(u8::is_ascii_whitespace) {
+            return 1;
+        }
+        self.delimiters
+            .iter()
+            .find_map(|delimiter| {
+                let mut buffer = [0; 4];
+                let encoded = delimiter.encode_utf8(&mut buffer).as_bytes();
+                bytes.starts_with(encoded).then_some(encoded.len())
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns number of input bytes after the last returned token.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Returns `true` if input bytes in `range` are all separators.
+    pub fn is_whitespace(&self, range: Range<usize>) -> bool {
+        let mut offset = range.start;
+        while offset < range.end {
+            match self.delimiter_len(offset) {
+                0 => return false,
+                len => offset += len,
+            }
+        }
+        true
+    }
+
+    /// Returns input text in `range`, empty if it is not valid UTF-8.
+    pub fn text(&self, range: Range<usize>) -> &'a str {
+        str::from_utf8(&self.bytes[range]).unwrap_or_default()
+    }
+
+    /// Returns the position right after the last returned token.
+    pub fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.offset - self.line_start + 1,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.bytes;
+        loop {
+            let byte = bytes.get(self.offset)?;
+            if bytes[self.offset..].starts_with(b"/*") {
+                let start = self.offset;
+                let Some(len) = bytes[start + 2..].windows(2).position(|w| w == b"*/") else {
+                    // Unterminated block comment is reported as a `/*` token.
+                    self.offset = bytes.len();
+                    let column = start - self.line_start + 1;
+                    return Some(Token::new(
+                        &bytes[start..start + 2],
+                        start,
+                        self.line,
+                        column,
+                    ));
+                };
+                let end = start + 2 + len + 2;
+                for (offset, byte) in bytes.iter().enumerate().take(end).skip(start) {
+                    if *byte == b'\n' {
+                        self.line += 1;
+                        self.line_start = offset + 1;
+                    }
+                }
+                self.offset = end;
+                continue;
+            }
+            let len = self.delimiter_len(self.offset);
+            if len == 0 {
+                break;
+            }
+            self.offset += len;
+            if *byte == b'\n' {
+                self.line += 1;
+                self.line_start = self.offset;
+            }
+        }
+
+        let start = self.offset;
+        let line_comment = is_line_comment(&bytes[start..], self.comment_prefixes);
+        while let Some(byte) = bytes.get(self.offset) {
+            let end = if line_comment {
+                *byte == b'\n'
+            } else {
+                self.delimiter_len(self.offset) > 0 || bytes[self.offset..].starts_with(b"/*")
+            };
+            if end {
+                break;
+            }
+            self.offset += 1;
+        }
+        let mut token = &bytes[start..self.offset];
+        if line_comment {
+            token = token.trim_ascii_end();
+        }
+        Some(Token::new(
+            token,
+            start,
+            self.line,
+            start - self.line_start + 1,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_comments() {
+        let mut tokenizer = Tokenizer::new(b"0A/* 0B\n */0C //*x\n/*/ */ 0D /* 0E");
+        let texts: [_; 5] = core::array::from_fn(|_| tokenizer.next().map(|t| (t.text, t.line)));
+        assert_eq!(
+            texts,
+            [
+                Some(("0A", 1)),
+                Some(("0C", 2)),
+                Some(("//*x", 2)),
+                Some(("0D", 3)),
+                Some(("/*", 3))
+            ]
+        );
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn test_delimiters() {
+        let mut tokenizer =
+            Tokenizer::new("0A,0B,\u{A0}0C //a,b".as_bytes()).with_delimiters(&[',', '\u{A0}']);
+        let texts: [_; 4] = core::array::from_fn(|_| tokenizer.next().map(|t| t.text));
+        assert_eq!(texts, [Some("0A"), Some("0B"), Some("0C"), Some("//a,b")]);
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn test_line_comments() {
+        let mut tokenizer =
+            Tokenizer::new(b"09 A0 // patched value \r\n0B # 0C\n0D").with_comment_prefixes(&["#"]);
+        let texts: [_; 6] = core::array::from_fn(|_| tokenizer.next().map(|t| (t.text, t.line)));
+        assert_eq!(
+            texts,
+            [
+                Some(("09", 1)),
+                Some(("A0", 1)),
+                Some(("// patched value", 1)),
+                Some(("0B", 2)),
+                Some(("# 0C", 2)),
+                Some(("0D", 3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        let mut tokenizer = Tokenizer::new(b"@10\r\n  0A\t0B\n\n0C \xFF0D");
+        let token = |text, offset, line, column| {
+            Some(Token {
+                text,
+                non_ascii: false,
+                offset,
+                line,
+                column,
+            })
+        };
+        assert_eq!(tokenizer.next(), token("@10", 0, 1, 1));
+        assert_eq!(tokenizer.next(), token("0A", 7, 2, 3));
+        assert_eq!(tokenizer.next(), token("0B", 10, 2, 6));
+        assert_eq!(
+            tokenizer.position(),
+            Position {
+                line: 2,
+                column: 8,
+                offset: 12
+            }
+        );
+        assert_eq!(tokenizer.next(), token("0C", 14, 4, 1));
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                text: "",
+                non_ascii: true,
+                offset: 17,
+                line: 4,
+                column: 4
+            })
+        );
+        assert_eq!(tokenizer.next(), None);
+    }
+}
@@ -0,0 +1,1608 @@
+//! Writer producing Verilog hex text, the inverse of [`crate::Reader`].
+//!
+//! # Writing bytes example:
+//!
+//! ```ignore
+//! let mut writer = crate::Writer::new(std::string::String::new());
+//! writer.write_bytes([(0x81000000, 0x09), (0x81000001, 0xA0), (0x81000080, 0x79)])?;
+//! std::print!("{}", writer.finish()?);
+//! ```
+//!
+//! Output:
+//! ```ignore
+//! @81000000
+//! 09 A0
+//! @81000080
+//! 79
+//! ```
+
+use core::error::Error;
+use core::fmt;
+
+use crate::checksum::FOOTER_PREFIX;
+use crate::{
+    Addr, AddrOffset, ByteTransform, Checksum, ChecksumAlgorithm, Endian, HexCase, OwnedParseError,
+    ParseError, Reader, Record, split_data,
+};
+
+/// Widest word the writer can group bytes into.
+const MAX_WORD_BYTES: usize = 32;
+
+/// Line terminator of generated text.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n` as expected by some Windows tools.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the line terminator.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Configuration options for the writer.
+///
+/// New options may be added, construct it with [`WriterOptions::builder`]
+/// or start from `WriterOptions::default()`.
+///
+/// ```ignore
+/// let options = crate::WriterOptions::builder()
+///     .bytes_per_line(32)
+///     .hex_case(crate::HexCase::Lower)
+///     .build();
+/// ```
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct WriterOptions<'a> {
+    /// Number of bytes per line, 16 by default same as `objcopy -O verilog`.
+    pub bytes_per_line: usize,
+    /// Case of hex digits in addresses and data.
+    pub hex_case: HexCase,
+    /// Group 1..32 bytes into a single token, 1 by default,
+    /// e.g. 32 for a 256-bit wide memory row.
+    pub word_bytes: usize,
+    /// Byte order of grouped tokens. A short big-endian word at the end of a segment
+    /// is padded with `fill_byte`, so its bytes keep their place in the token.
+    pub endian: Endian,
+    /// Symbol resolver, a `// <symbol>` comment is emitted before every line
+    /// whose start address resolves to a symbol, e.g. from a symbol table
+    /// loaded at runtime.
+    pub symbol_resolver: Option<&'a dyn Fn(Addr) -> Option<&'a str>>,
+    /// Gaps of up to this many bytes are filled with `fill_byte`
+    /// instead of starting a new `@address` line, 0 by default.
+    pub max_fill_gap: Addr,
+    /// Value of the bytes filling small gaps.
+    pub fill_byte: u8,
+    /// Append a `// 0x81000040` comment with the start address to every data line.
+    pub address_comments: bool,
+    /// Check address order while writing: fail with [`WriterError::Unsorted`] or
+    /// [`WriterError::Overlap`] instead of emitting a byte that is out of order.
+    /// The check is streaming, bytes before the offending one are already emitted.
+    pub check_order: bool,
+    /// Emit `@address` in units of `word_bytes` as `$readmemh` expects for wide memories,
+    /// segments must start at word-aligned addresses.
+    pub word_addresses: bool,
+    /// Append a `// checksum: XXXXXXXX` comment computed over all emitted data bytes,
+    /// see [`crate::verify_checksum`].
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// Line terminator, `\n` by default.
+    pub line_ending: LineEnding,
+    /// Transform applied to every byte, undone by the same
+    /// [`crate::ReaderOptions::transform`] when reading.
+    pub transform: ByteTransform,
+    /// Memory depth in words of `word_bytes`, the tail after the last byte
+    /// is padded with `fill_byte` up to `depth * word_bytes`.
+    /// Holes are padded too when `max_fill_gap` covers them.
+    pub depth: Option<Addr>,
+    /// Added to the address of every written byte, e.g. to rebase an image from VMA to 0.
+    pub addr_offset: AddrOffset,
+}
+
+impl Default for WriterOptions<'_> {
+    fn default() -> Self {
+        WriterOptions {
+            bytes_per_line: 16,
+            hex_case: HexCase::Upper,
+            word_bytes: 1,
+            endian: Endian::Little,
+            symbol_resolver: None,
+            max_fill_gap: 0,
+            fill_byte: 0,
+            address_comments: false,
+            check_order: false,
+            word_addresses: false,
+            checksum: None,
+            line_ending: LineEnding::Lf,
+            transform: ByteTransform::None,
+            depth: None,
+            addr_offset: 0,
+        }
+    }
+}
+
+/// Writer error type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WriterError {
+    /// Output sink failed.
+    Format,
+    /// Byte at `addr` comes after byte at higher address `previous`.
+    Unsorted { addr: Addr, previous: Addr },
+    /// Byte at `addr` overlaps data already written in the current segment.
+    Overlap { addr: Addr },
+    /// Segment starting at `addr` is not aligned to the word size.
+    Unaligned { addr: Addr },
+    /// [`ShardWriter`] maximum size is below `min_size` bytes needed for the options.
+    ShardTooSmall { min_size: usize },
+}
+
+impl fmt::Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WriterError::Format => write!(f, "failed to write output"),
+            WriterError::Unsorted { addr, previous } => {
+                write!(f, "address {addr:#010X} comes after {previous:#010X}")
+            }
+            WriterError::Overlap { addr } => write!(f, "address {addr:#010X} is written twice"),
+            WriterError::Unaligned { addr } => {
+                write!(f, "address {addr:#010X} is not aligned to word size")
+            }
+            WriterError::ShardTooSmall { min_size } => {
+                write!(f, "shard size must be at least {min_size} bytes")
+            }
+        }
+    }
+}
+
+impl Error for WriterError {}
+
+impl From<fmt::Error> for WriterError {
+    fn from(_: fmt::Error) -> Self {
+        WriterError::Format
+    }
+}
+
+impl<'a> WriterOptions<'a> {
+    /// Returns a builder starting from the default options.
+    pub fn builder() -> WriterOptionsBuilder<'a> {
+        WriterOptionsBuilder {
+            options: Default::default(),
+        }
+    }
+}
+
+/// Builder for [`WriterOptions`], see [`WriterOptions::builder`].
+#[derive(Clone)]
+pub struct WriterOptionsBuilder<'a> {
+    options: WriterOptions<'a>,
+}
+
+impl<'a> WriterOptionsBuilder<'a> {
+    /// Number of bytes per line.
+    pub fn bytes_per_line(mut self, bytes_per_line: usize) -> Self {
+        self.options.bytes_per_line = bytes_per_line;
+        self
+    }
+
+    /// Case of hex digits in addresses and data.
+    pub fn hex_case(mut self, hex_case: HexCase) -> Self {
+        self.options.hex_case = hex_case;
+        self
+    }
+
+    /// Group 1..32 bytes into a single token.
+    pub fn word_bytes(mut self, word_bytes: usize) -> Self {
+        self.options.word_bytes = word_bytes;
+        self
+    }
+
+    /// Byte order of grouped tokens.
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.options.endian = endian;
+        self
+    }
+
+    /// Symbol resolver annotating lines with `// <symbol>` comments.
+    pub fn symbol_resolver(mut self, symbol_resolver: &'a dyn Fn(Addr) -> Option<&'a str>) -> Self {
+        self.options.symbol_resolver = Some(symbol_resolver);
+        self
+    }
+
+    /// Fill gaps of up to `max_fill_gap` bytes with `fill_byte`.
+    pub fn fill_gaps(mut self, max_fill_gap: Addr, fill_byte: u8) -> Self {
+        self.options.max_fill_gap = max_fill_gap;
+        self.options.fill_byte = fill_byte;
+        self
+    }
+
+    /// Append a start address comment to every data line.
+    pub fn address_comments(mut self, address_comments: bool) -> Self {
+        self.options.address_comments = address_comments;
+        self
+    }
+
+    /// Check while writing that bytes come in address order without overlaps.
+    pub fn check_order(mut self, check_order: bool) -> Self {
+        self.options.check_order = check_order;
+        self
+    }
+
+    /// Emit `@address` in word units.
+    pub fn word_addresses(mut self, word_addresses: bool) -> Self {
+        self.options.word_addresses = word_addresses;
+        self
+    }
+
+    /// Append a checksum footer comment.
+    pub fn checksum(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.options.checksum = Some(algorithm);
+        self
+    }
+
+    /// Line terminator.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.options.line_ending = line_ending;
+        self
+    }
+
+    /// Transform applied to every byte.
+    pub fn transform(mut self, transform: ByteTransform) -> Self {
+        self.options.transform = transform;
+        self
+    }
+
+    /// Pad the tail up to memory `depth` words with `fill_byte`.
+    pub fn pad_to_depth(mut self, depth: Addr, fill_byte: u8) -> Self {
+        self.options.depth = Some(depth);
+        self.options.fill_byte = fill_byte;
+        self
+    }
+
+    /// Offset added to every address.
+    pub fn addr_offset(mut self, addr_offset: AddrOffset) -> Self {
+        self.options.addr_offset = addr_offset;
+        self
+    }
+
+    /// Returns the configured options.
+    pub fn build(self) -> WriterOptions<'a> {
+        self.options
+    }
+}
+
+/// A writer for Verilog hex files.
+///
+/// Text is emitted into any [`core::fmt::Write`] sink, e.g. `String` or a UART driver.
+/// Bytes are expected in address order; whenever the address of the next byte
+/// is not the successor of the previous one, a new `@address` line is emitted.
+/// Contiguous data continues on the current line, no matter how it is split
+/// between calls or records, same as `objcopy` output.
+pub struct Writer<'a, W: fmt::Write> {
+    /// Output sink.
+    output: W,
+    /// Address the next byte is expected at, `None` before the first byte
+    /// and after the last address of the address space.
+    next_addr: Option<Addr>,
+    /// Address of the last written byte.
+    last_addr: Option<Addr>,
+    /// Number of bytes already written on the current line.
+    bytes_in_line: usize,
+    /// Bytes of the word being accumulated, in address order.
+    word: [u8; MAX_WORD_BYTES],
+    /// Number of bytes accumulated in `word`.
+    word_len: usize,
+    /// Address of the first byte in `word`.
+    word_addr: Addr,
+    /// Address of the first byte on the current line.
+    line_addr: Addr,
+    /// Address of the first byte after the last `@address` line.
+    segment_addr: Addr,
+    /// Address used by [`Writer::write_byte`].
+    cursor: Addr,
+    /// Checksum of emitted data.
+    checksum: Checksum,
+    /// Configuration options.
+    options: WriterOptions<'a>,
+}
+
+impl<'a, W: fmt::Write> Writer<'a, W> {
+    /// Create a new writer into `output` with the specified options.
+    pub fn new_with_options(output: W, options: WriterOptions<'a>) -> Self {
+        Writer {
+            output,
+            next_addr: None,
+            last_addr: None,
+            bytes_in_line: 0,
+            word: [0; MAX_WORD_BYTES],
+            word_len: 0,
+            word_addr: 0,
+            line_addr: 0,
+            segment_addr: 0,
+            cursor: 0,
+            checksum: Checksum::new(options.checksum.unwrap_or_default()),
+            options,
+        }
+    }
+
+    /// Create a new writer into `output` with default options.
+    pub fn new(output: W) -> Self {
+        Writer::new_with_options(output, Default::default())
+    }
+
+    /// Write `(address, byte)` pairs.
+    pub fn write_bytes<I>(&mut self, bytes: I) -> Result<(), WriterError>
+    where
+        I: IntoIterator<Item = (Addr, u8)>,
+    {
+        for (addr, value) in bytes {
+            self.write_byte_at(addr, value)?;
+        }
+        Ok(())
+    }
+
+    /// Set address of the next byte written with [`Writer::write_byte`] or [`Writer::write_slice`].
+    ///
+    /// Nothing is emitted until data follows, setting the address the data
+    /// would continue at anyway does not start a new `@address` line.
+    pub fn set_address(&mut self, addr: Addr) {
+        self.cursor = addr;
+    }
+
+    /// Write a byte at the current address and advance the address.
+    ///
+    /// ```ignore
+    /// let mut writer = crate::Writer::new(String::new());
+    /// writer.set_address(0x81000000);
+    /// writer.write_byte(0x09)?;
+    /// writer.write_slice(&[0xA0, 0xF3])?;
+    /// let text = writer.finish()?;
+    /// ```
+    pub fn write_byte(&mut self, value: u8) -> Result<(), WriterError> {
+        self.write_byte_at(self.cursor, value)
+    }
+
+    /// Write bytes starting at the current address and advance the address.
+    pub fn write_slice(&mut self, values: &[u8]) -> Result<(), WriterError> {
+        values.iter().try_for_each(|value| self.write_byte(*value))
+    }
+
+    /// Write records as produced by [`crate::Reader`].
+    ///
+    /// Grouped data is split back into bytes, lowest address first.
+    /// Comments and end-of-file records are ignored.
+    pub fn write_records<'r, I>(&mut self, records: I) -> Result<(), WriterError>
+    where
+        I: IntoIterator<Item = Record<'r>>,
+    {
+        for record in records {
+            if let Record::Data { addr, value } = record {
+                let (value, len) = split_data(value);
+                for i in 0..len {
+                    self.write_byte_at(addr + i as Addr, (value >> (8 * i)) as u8)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a `//` comment on its own line, e.g. a header banner or a section name.
+    ///
+    /// Every line of a multi-line `text` becomes a separate comment line.
+    pub fn write_comment(&mut self, text: &str) -> Result<(), WriterError> {
+        self.flush_word()?;
+        self.end_line()?;
+        for line in text.lines() {
+            if line.is_empty() {
+                self.output.write_str("//")?;
+            } else {
+                write!(self.output, "// {line}")?;
+            }
+            self.new_line()?;
+        }
+        Ok(())
+    }
+
+    /// Terminate the last line and return the output sink.
+    pub fn finish(mut self) -> Result<W, WriterError> {
+        self.terminate()?;
+        Ok(self.output)
+    }
+
+    fn terminate(&mut self) -> Result<(), WriterError> {
+        if let Some(depth) = self.options.depth {
+            let end = depth.saturating_mul(self.word_bytes() as Addr);
+            for addr in self.next_addr.unwrap_or(0)..end {
+                self.push_byte(addr, self.options.fill_byte)?;
+            }
+        }
+
+        self.flush_word()?;
+        self.end_line()?;
+        if self.options.checksum.is_some() {
+            write!(self.output, "{FOOTER_PREFIX} ")?;
+            for byte in self.checksum.digest().as_bytes() {
+                self.options
+                    .hex_case
+                    .write_hex(&mut self.output, u64::from(*byte), 2)?;
+            }
+            self.new_line()?;
+        }
+        Ok(())
+    }
+
+    fn write_byte_at(&mut self, addr: Addr, value: u8) -> Result<(), WriterError> {
+        self.cursor = addr.wrapping_add(1);
+        let addr = addr.wrapping_add_signed(self.options.addr_offset);
+
+        if self.options.check_order
+            && let Some(last_addr) = self.last_addr
+            && addr <= last_addr
+        {
+            return Err(if addr >= self.segment_addr {
+                WriterError::Overlap { addr }
+            } else {
+                WriterError::Unsorted {
+                    addr,
+                    previous: last_addr,
+                }
+            });
+        }
+
+        if let Some(next_addr) = self.next_addr
+            && addr > next_addr
+            && addr - next_addr <= self.options.max_fill_gap
+        {
+            for fill_addr in next_addr..addr {
+                self.push_byte(fill_addr, self.options.fill_byte)?;
+            }
+        }
+        self.push_byte(addr, value)
+    }
+
+    fn push_byte(&mut self, addr: Addr, value: u8) -> Result<(), WriterError> {
+        if self.next_addr != Some(addr) {
+            let word_bytes = self.word_bytes() as Addr;
+            if self.options.word_addresses && !addr.is_multiple_of(word_bytes) {
+                return Err(WriterError::Unaligned { addr });
+            }
+
+            self.flush_word()?;
+            self.end_line()?;
+            self.segment_addr = addr;
+            self.output.write_char('@')?;
+            let addr = if self.options.word_addresses {
+                addr / word_bytes
+            } else {
+                addr
+            };
+            self.options.hex_case.write_hex(&mut self.output, addr, 8)?;
+            self.new_line()?;
+        }
+
+        if self.word_len == 0 {
+            self.word_addr = addr;
+        }
+        let value = self.options.transform.apply(value);
+        if self.options.checksum.is_some() {
+            self.checksum.update(&[value]);
+        }
+        self.word[self.word_len] = value;
+        self.word_len += 1;
+        self.next_addr = addr.checked_add(1);
+        self.last_addr = Some(addr);
+
+        if self.word_len >= self.word_bytes() {
+            self.flush_word()?;
+        }
+        Ok(())
+    }
+
+    fn word_bytes(&self) -> usize {
+        self.options.word_bytes.clamp(1, MAX_WORD_BYTES)
+    }
+
+    /// Emit the accumulated word as a single token. A short little-endian word is emitted
+    /// with fewer digits, a short big-endian word is padded with `fill_byte`.
+    fn flush_word(&mut self) -> fmt::Result {
+        if self.word_len == 0 {
+            return Ok(());
+        }
+
+        if self.options.endian == Endian::Big && self.word_len < self.word_bytes() {
+            let value = self.options.transform.apply(self.options.fill_byte);
+            while self.word_len < self.word_bytes() {
+                if self.options.checksum.is_some() {
+                    self.checksum.update(&[value]);
+                }
+                self.word[self.word_len] = value;
+                self.word_len += 1;
+            }
+            // The next byte starts a new segment after the padding.
+            self.next_addr = self.word_addr.checked_add(self.word_len as Addr);
+        }
+
+        if self.bytes_in_line >= self.options.bytes_per_line.max(1) {
+            self.end_line()?;
+        }
+
+        if self.bytes_in_line > 0 {
+            self.output.write_char(' ')?;
+        } else {
+            self.line_addr = self.word_addr;
+            if let Some(name) = self
+                .options
+                .symbol_resolver
+                .and_then(|resolve| resolve(self.word_addr))
+            {
+                write!(self.output, "// <{name}>")?;
+                self.new_line()?;
+            }
+        }
+
+        let word = &self.word[..self.word_len];
+        let hex_case = self.options.hex_case;
+        let output = &mut self.output;
+        let mut write_byte = |value: &u8| hex_case.write_hex(output, u64::from(*value), 2);
+        match self.options.endian {
+            Endian::Little => word.iter().rev().try_for_each(&mut write_byte)?,
+            Endian::Big => word.iter().try_for_each(&mut write_byte)?,
+        }
+
+        self.bytes_in_line += self.word_len;
+        self.word_len = 0;
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> fmt::Result {
+        self.output.write_str(self.options.line_ending.as_str())
+    }
+
+    fn end_line(&mut self) -> fmt::Result {
+        if self.bytes_in_line > 0 {
+            if self.options.address_comments {
+                self.output.write_str(" // 0x")?;
+                self.options
+                    .hex_case
+                    .write_hex(&mut self.output, self.line_addr, 8)?;
+            }
+            self.new_line()?;
+            self.bytes_in_line = 0;
+        }
+        Ok(())
+    }
+}
+
+/// A writer splitting one image into `N` byte lanes, e.g. for a 32-bit memory
+/// built from four byte-wide BRAMs.
+///
+/// The byte at address `addr` goes to lane `addr % N` at lane-local address `addr / N`,
+/// every lane output is a valid Verilog hex file on its own.
+///
+/// ```ignore
+/// let mut writer = crate::LaneWriter::new([(); 4].map(|_| String::new()));
+/// writer.write_bytes(bytes)?;
+/// let [lane0, lane1, lane2, lane3] = writer.finish()?;
+/// ```
+pub struct LaneWriter<'a, W: fmt::Write, const N: usize> {
+    lanes: [Writer<'a, W>; N],
+}
+
+impl<'a, W: fmt::Write, const N: usize> LaneWriter<'a, W, N> {
+    /// Create a new lane writer into `outputs` with the specified options for every lane.
+    pub fn new_with_options(outputs: [W; N], options: WriterOptions<'a>) -> Self {
+        const { assert!(N > 0, "at least one lane is required") };
+        LaneWriter {
+            lanes: outputs.map(|output| Writer::new_with_options(output, options.clone())),
+        }
+    }
+
+    /// Create a new lane writer into `outputs` with default options.
+    pub fn new(outputs: [W; N]) -> Self {
+        LaneWriter::new_with_options(outputs, Default::default())
+    }
+
+    /// Write `(address, byte)` pairs.
+    pub fn write_bytes<I>(&mut self, bytes: I) -> Result<(), WriterError>
+    where
+        I: IntoIterator<Item = (Addr, u8)>,
+    {
+        for (addr, value) in bytes {
+            self.lanes[(addr % N as Addr) as usize].write_byte_at(addr / N as Addr, value)?;
+        }
+        Ok(())
+    }
+
+    /// Write records as produced by [`crate::Reader`].
+    pub fn write_records<'r, I>(&mut self, records: I) -> Result<(), WriterError>
+    where
+        I: IntoIterator<Item = Record<'r>>,
+    {
+        for record in records {
+            if let Record::Data { addr, value } = record {
+                let (value, len) = split_data(value);
+                self.write_bytes((0..len).map(|i| (addr + i as Addr, (value >> (8 * i)) as u8)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Terminate the last line of every lane and return the output sinks.
+    pub fn finish(mut self) -> Result<[W; N], WriterError> {
+        for lane in &mut self.lanes {
+            lane.terminate()?;
+        }
+        Ok(self.lanes.map(|lane| lane.output))
+    }
+}
+
+/// Error of [`transcode`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TranscodeError {
+    /// Failed to parse input.
+    Reader(OwnedParseError),
+    /// Failed to write output.
+    Writer(WriterError),
+    /// Byte at `addr` has `x` or `z` digits, which can't be written.
+    Unknown { addr: Addr },
+}
+
+impl fmt::Display for TranscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TranscodeError::Reader(error) => write!(f, "read error: {error}"),
+            TranscodeError::Writer(error) => write!(f, "write error: {error}"),
+            TranscodeError::Unknown { addr } => {
+                write!(f, "byte at address {addr:#010X} has unknown digits")
+            }
+        }
+    }
+}
+
+impl Error for TranscodeError {}
+
+impl From<OwnedParseError> for TranscodeError {
+    fn from(error: OwnedParseError) -> Self {
+        TranscodeError::Reader(error)
+    }
+}
+
+impl From<ParseError<'_>> for TranscodeError {
+    fn from(error: ParseError<'_>) -> Self {
+        TranscodeError::Reader(error.into())
+    }
+}
+
+impl From<WriterError> for TranscodeError {
+    fn from(error: WriterError) -> Self {
+        TranscodeError::Writer(error)
+    }
+}
+
+/// Stream every record of `reader` straight into `writer` and return the output sink.
+///
+/// Memory use is constant no matter how large the image is; the layout is changed
+/// with the writer options, e.g. `word_bytes` to regroup or `addr_offset` to rebase.
+/// [`Record::DataLine`] is decoded into bytes, [`Record::Unknown`] is an error.
+///
+/// ```ignore
+/// let options = crate::WriterOptions::builder().word_bytes(4).addr_offset(-0x8100_0000).build();
+/// let text = crate::transcode(crate::Reader::new(input), crate::Writer::new_with_options(String::new(), options))?;
+/// ```
+pub fn transcode<W: fmt::Write>(
+    reader: Reader<'_>,
+    mut writer: Writer<'_, W>,
+) -> Result<W, TranscodeError> {
+    for record in reader.data_records() {
+        match record? {
+            Record::Unknown { addr, .. } => return Err(TranscodeError::Unknown { addr }),
+            record => writer.write_records([record])?,
+        }
+    }
+    Ok(writer.finish()?)
+}
+
+/// Text of one output file produced by [`ShardWriter`].
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq)]
+pub struct Shard {
+    /// Address of the first byte in the shard.
+    pub addr: Addr,
+    /// Verilog hex text starting with `@address`.
+    pub text: std::string::String,
+}
+
+/// Returns worst case number of characters one more byte and finishing a shard
+/// can add with `options`, not counting symbol comments.
+#[cfg(feature = "std")]
+fn shard_reserve(options: &WriterOptions) -> usize {
+    let line_ending = options.line_ending.as_str().len();
+    let addr_digits = (2 * core::mem::size_of::<Addr>()).max(8);
+    let address_comment = if options.address_comments {
+        " // 0x".len() + addr_digits
+    } else {
+        0
+    };
+    let line_end = address_comment + line_ending;
+    let address_line = 1 + addr_digits + line_ending;
+    let word = 1 + 2 * options.word_bytes.clamp(1, MAX_WORD_BYTES);
+    let footer = options.checksum.map_or(0, |algorithm| {
+        let digest_len = Checksum::new(algorithm).digest().as_bytes().len();
+        FOOTER_PREFIX.len() + 1 + 2 * digest_len + line_ending
+    });
+    // Ending the current line, starting a new segment with the byte completing
+    // a word, then ending the last line and the footer.
+    2 * line_end + address_line + word + footer
+}
+
+/// A writer splitting a large image into multiple Verilog hex files of bounded size,
+/// e.g. for a flash programmer accepting files up to 1 MiB.
+///
+/// ```ignore
+/// let mut writer = crate::ShardWriter::new(1 << 20, Default::default())?;
+/// writer.write_bytes(bytes)?;
+/// for shard in writer.finish()? {
+///     std::fs::write(std::format!("image_{:08X}.hex", shard.addr), shard.text)?;
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub struct ShardWriter<'a> {
+    /// Maximum size of a shard in bytes.
+    max_size: usize,
+    /// Characters kept free for one more byte and finishing the shard.
+    reserve: usize,
+    /// Options of the whole image, gap filling and depth padding are done here.
+    options: WriterOptions<'a>,
+    /// Writer of the current shard.
+    writer: Writer<'a, std::string::String>,
+    /// Start address of the current shard, `None` if it is empty.
+    shard_addr: Option<Addr>,
+    /// Address the next byte is expected at.
+    next_addr: Option<Addr>,
+    /// Completed shards.
+    shards: std::vec::Vec<Shard>,
+}
+
+#[cfg(feature = "std")]
+impl<'a> ShardWriter<'a> {
+    /// Create a new writer of shards up to `max_size` bytes.
+    ///
+    /// Fails with [`WriterError::ShardTooSmall`] if `max_size` can't hold one byte
+    /// with the `@address` line, word and footer the options call for.
+    /// Symbol comments are not accounted for and can make shards longer.
+    pub fn new(max_size: usize, options: WriterOptions<'a>) -> Result<Self, WriterError> {
+        let reserve = shard_reserve(&options);
+        if max_size < reserve {
+            return Err(WriterError::ShardTooSmall { min_size: reserve });
+        }
+        Ok(ShardWriter {
+            max_size,
+            reserve,
+            writer: Writer::new_with_options(std::string::String::new(), shard_options(&options)),
+            options,
+            shard_addr: None,
+            next_addr: None,
+            shards: std::vec::Vec::new(),
+        })
+    }
+
+    /// Write `(address, byte)` pairs.
+    pub fn write_bytes<I>(&mut self, bytes: I) -> Result<(), WriterError>
+    where
+        I: IntoIterator<Item = (Addr, u8)>,
+    {
+        for (addr, value) in bytes {
+            if let Some(next_addr) = self.next_addr
+                && addr > next_addr
+                && addr - next_addr <= self.options.max_fill_gap
+            {
+                for fill_addr in next_addr..addr {
+                    self.push_byte(fill_addr, self.options.fill_byte)?;
+                }
+            }
+            self.push_byte(addr, value)?;
+        }
+        Ok(())
+    }
+
+    /// Pad up to the memory depth, complete the last shard and return all shards
+    /// in the order they were written.
+    pub fn finish(mut self) -> Result<std::vec::Vec<Shard>, WriterError> {
+        if let Some(depth) = self.options.depth {
+            let word_bytes = self.options.word_bytes.clamp(1, MAX_WORD_BYTES) as Addr;
+            for addr in self.next_addr.unwrap_or(0)..depth.saturating_mul(word_bytes) {
+                self.push_byte(addr, self.options.fill_byte)?;
+            }
+        }
+        if self.shard_addr.is_some() {
+            self.cut()?;
+        }
+        Ok(self.shards)
+    }
+
+    fn push_byte(&mut self, addr: Addr, value: u8) -> Result<(), WriterError> {
+        if self.shard_addr.is_some() && self.writer.output.len() + self.reserve > self.max_size {
+            self.cut()?;
+        }
+        self.shard_addr.get_or_insert(addr);
+        self.next_addr = addr.checked_add(1);
+        self.writer.write_byte_at(addr, value)
+    }
+
+    fn cut(&mut self) -> Result<(), WriterError> {
+        let writer = core::mem::replace(
+            &mut self.writer,
+            Writer::new_with_options(std::string::String::new(), shard_options(&self.options)),
+        );
+        if let Some(addr) = self.shard_addr.take() {
+            self.shards.push(Shard {
+                addr,
+                text: writer.finish()?,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Returns options of a single shard, [`ShardWriter`] fills gaps and pads itself
+/// so that the fill bytes are split between shards too.
+#[cfg(feature = "std")]
+fn shard_options<'a>(options: &WriterOptions<'a>) -> WriterOptions<'a> {
+    WriterOptions {
+        max_fill_gap: 0,
+        depth: None,
+        ..options.clone()
+    }
+}
+
+/// Re-emit Verilog hex `text` in canonical form so that diffs only show real data changes.
+///
+/// Data is sorted by address, a byte written twice keeps the last value, lines are
+/// 16 bytes wide with upper case digits and `@address` lines appear only where
+/// the address jumps. Comments are dropped.
+#[cfg(feature = "std")]
+pub fn normalize(text: &str) -> Result<std::string::String, crate::OwnedParseError> {
+    let mut bytes = std::collections::BTreeMap::new();
+    for record in crate::Reader::new(text) {
+        if let Record::Data { addr, value } = record? {
+            let (value, len) = split_data(value);
+            for i in 0..len {
+                bytes.insert(addr + i as Addr, (value >> (8 * i)) as u8);
+            }
+        }
+    }
+
+    let mut writer = Writer::new(std::string::String::new());
+    writer
+        .write_bytes(bytes)
+        .expect("writing to String can't fail");
+    Ok(writer.finish().expect("writing to String can't fail"))
+}
+
+/// Adapter using a [`std::io::Write`] as output of [`Writer`].
+///
+/// Output is buffered and streamed to `inner`, so large images are never held in memory.
+/// The I/O error behind a failed write is kept and returned by [`IoWriter::into_inner`].
+///
+/// ```ignore
+/// let file = std::fs::File::create("image.hex")?;
+/// let mut writer = veriloghex::Writer::new(veriloghex::IoWriter::new(file));
+/// writer.write_bytes(bytes)?;
+/// let file = writer.finish()?.into_inner()?;
+/// ```
+#[cfg(feature = "std")]
+pub struct IoWriter<W: std::io::Write> {
+    /// Buffered output.
+    inner: std::io::BufWriter<W>,
+    /// First I/O error, `fmt::Write` can't carry it.
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> IoWriter<W> {
+    /// Create a new buffered adapter over `inner`.
+    pub fn new(inner: W) -> Self {
+        IoWriter {
+            inner: std::io::BufWriter::new(inner),
+            error: None,
+        }
+    }
+
+    /// Flush buffered output and return the underlying writer,
+    /// or the I/O error that made an earlier write fail.
+    pub fn into_inner(self) -> std::io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.inner.into_inner().map_err(|error| error.into_error())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        use std::io::Write;
+
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    #[test]
+    fn test_write_bytes() {
+        let mut writer = Writer::new(String::new());
+        writer
+            .write_bytes((0..18).map(|i| (0x81000000 + i, i as u8)))
+            .unwrap();
+        writer.write_bytes([(0x81000080, 0x79)]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@81000000\n\
+             00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F\n\
+             10 11\n\
+             @81000080\n\
+             79\n"
+        );
+    }
+
+    #[test]
+    fn test_contiguous_chunks() {
+        let mut writer = Writer::new(String::new());
+        writer.write_bytes([(0x10, 0x01), (0x11, 0x02)]).unwrap();
+        writer.write_bytes([(0x12, 0x03)]).unwrap();
+        writer
+            .write_records([
+                Record::NewAddress(0x13),
+                Record::Data {
+                    addr: 0x13,
+                    value: crate::DataType::U16(0x0504),
+                },
+            ])
+            .unwrap();
+        writer.write_bytes([(Addr::MAX, 0xFF), (0, 0x00)]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            std::format!(
+                "@00000010\n01 02 03 04 05\n@{:08X}\nFF\n@00000000\n00\n",
+                Addr::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_comment() {
+        let mut writer = Writer::new(String::new());
+        writer.write_comment("Boot ROM\n\nbuild 42").unwrap();
+        writer.write_comment(".text section").unwrap();
+        writer.write_bytes([(0x10, 0x01), (0x11, 0x02)]).unwrap();
+        writer.write_comment(".data section").unwrap();
+        writer.write_bytes([(0x12, 0x03)]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "// Boot ROM\n//\n// build 42\n// .text section\n\
+             @00000010\n01 02\n// .data section\n03\n"
+        );
+    }
+
+    #[test]
+    fn test_symbol_resolver() {
+        // Symbol table loaded at runtime, e.g. from an ELF file.
+        let symbols = std::collections::BTreeMap::from([
+            (0x10, String::from("_start")),
+            (0x14, String::from("main")),
+        ]);
+        let resolve = |addr| symbols.get(&addr).map(String::as_str);
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                bytes_per_line: 2,
+                symbol_resolver: Some(&resolve),
+                ..Default::default()
+            },
+        );
+        writer
+            .write_bytes((0..6).map(|i| (0x10 + i, i as u8)))
+            .unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000010\n// <_start>\n00 01\n02 03\n// <main>\n04 05\n"
+        );
+    }
+
+    #[test]
+    fn test_fill_gap() {
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                max_fill_gap: 2,
+                fill_byte: 0xFF,
+                ..Default::default()
+            },
+        );
+        writer
+            .write_bytes([(0x10, 0x01), (0x13, 0x02), (0x17, 0x03)])
+            .unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000010\n01 FF FF 02\n@00000017\n03\n"
+        );
+    }
+
+    #[test]
+    fn test_address_comments() {
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                bytes_per_line: 4,
+                word_bytes: 2,
+                address_comments: true,
+                ..Default::default()
+            },
+        );
+        writer
+            .write_bytes((0..6).map(|i| (0x81000040 + i, i as u8)))
+            .unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@81000040\n0100 0302 // 0x81000040\n0504 // 0x81000044\n"
+        );
+    }
+
+    #[test]
+    fn test_options_builder() {
+        let options = WriterOptions::builder()
+            .bytes_per_line(2)
+            .hex_case(HexCase::Lower)
+            .word_bytes(2)
+            .endian(Endian::Big)
+            .fill_gaps(1, 0xEE)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer
+            .write_bytes([(0x10, 0xAB), (0x11, 0xCD), (0x13, 0xEF)])
+            .unwrap();
+        assert_eq!(writer.finish().unwrap(), "@00000010\nabcd\neeef\n");
+    }
+
+    #[test]
+    fn test_check_order() {
+        let options = WriterOptions::builder().check_order(true).build();
+        let mut writer = Writer::new_with_options(String::new(), options.clone());
+        assert_eq!(
+            writer.write_bytes([(0x10, 0x01), (0x11, 0x02), (0x11, 0x03)]),
+            Err(WriterError::Overlap { addr: 0x11 })
+        );
+        assert_eq!(writer.finish().unwrap(), "@00000010\n01 02\n");
+
+        let mut writer = Writer::new_with_options(String::new(), options.clone());
+        assert_eq!(
+            writer.write_bytes([(0x10, 0x01), (0x20, 0x02), (0x18, 0x03)]),
+            Err(WriterError::Unsorted {
+                addr: 0x18,
+                previous: 0x20
+            })
+        );
+
+        let mut writer = Writer::new_with_options(String::new(), options);
+        assert_eq!(
+            writer.write_bytes([(Addr::MAX, 0x01), (0x10, 0x02)]),
+            Err(WriterError::Unsorted {
+                addr: 0x10,
+                previous: Addr::MAX
+            })
+        );
+
+        let mut writer = Writer::new(String::new());
+        assert!(writer.write_bytes([(0x10, 0x01), (0x10, 0x02)]).is_ok());
+    }
+
+    #[test]
+    fn test_push_style() {
+        let mut writer = Writer::new(String::new());
+        writer.set_address(0x81000000);
+        writer.write_byte(0x09).unwrap();
+        writer.write_slice(&[0xA0, 0xF3]).unwrap();
+        writer.set_address(0x81000003);
+        writer.write_byte(0x22).unwrap();
+        writer.set_address(0x81000080);
+        writer.write_slice(&[0x79, 0x71]).unwrap();
+        writer.write_bytes([(0x81000100, 0x01)]).unwrap();
+        writer.write_byte(0x02).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@81000000\n09 A0 F3 22\n@81000080\n79 71\n@81000100\n01 02\n"
+        );
+    }
+
+    #[test]
+    fn test_word_addresses() {
+        let options = WriterOptions::builder()
+            .word_bytes(4)
+            .word_addresses(true)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options.clone());
+        writer
+            .write_bytes((0..4).map(|i| (0x40 + i, i as u8)))
+            .unwrap();
+        writer.write_bytes([(0x80, 0xAA)]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000010\n03020100\n@00000020\nAA\n"
+        );
+
+        let mut writer = Writer::new_with_options(String::new(), options);
+        assert_eq!(
+            writer.write_bytes([(0x42, 0x00)]),
+            Err(WriterError::Unaligned { addr: 0x42 })
+        );
+    }
+
+    #[test]
+    fn test_checksum_footer() {
+        let options = WriterOptions::builder()
+            .checksum(ChecksumAlgorithm::Crc32)
+            .fill_gaps(1, 0x33)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_slice(b"12").unwrap();
+        writer.set_address(3);
+        writer.write_slice(b"456789").unwrap();
+        let text = writer.finish().unwrap();
+        assert_eq!(
+            text,
+            "@00000000\n31 32 33 34 35 36 37 38 39\n// checksum: CBF43926\n"
+        );
+        assert_eq!(
+            crate::verify_checksum(&text, ChecksumAlgorithm::Crc32),
+            Ok(true)
+        );
+
+        let options = WriterOptions::builder()
+            .word_bytes(4)
+            .checksum(ChecksumAlgorithm::Crc32)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_slice(b"12345678").unwrap();
+        let text = writer.finish().unwrap();
+        assert_eq!(
+            text,
+            "@00000000\n34333231 38373635\n// checksum: 9AE0DAAF\n"
+        );
+        let options = crate::ReaderOptions {
+            token_bytes: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::verify_checksum_with_options(&text, ChecksumAlgorithm::Crc32, options),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let options = WriterOptions::builder()
+            .line_ending(LineEnding::CrLf)
+            .bytes_per_line(2)
+            .checksum(ChecksumAlgorithm::Sum32)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_comment("ROM").unwrap();
+        writer.write_slice(&[0x01, 0x02, 0x03]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "// ROM\r\n@00000000\r\n01 02\r\n03\r\n// checksum: 00000006\r\n"
+        );
+    }
+
+    #[test]
+    fn test_transform() {
+        let options = WriterOptions::builder()
+            .transform(ByteTransform::NibbleSwap)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_slice(&[0x12, 0xAB]).unwrap();
+        let text = writer.finish().unwrap();
+        assert_eq!(text, "@00000000\n21 BA\n");
+
+        let reader = crate::Reader::new_with_options(
+            &text,
+            crate::ReaderOptions {
+                transform: ByteTransform::NibbleSwap,
+                ..Default::default()
+            },
+        );
+        let values = reader.filter_map(|record| match record {
+            Ok(Record::Data {
+                addr: _,
+                value: crate::DataType::U8(value),
+            }) => Some(value),
+            _ => None,
+        });
+        assert!(values.eq([0x12, 0xAB]));
+    }
+
+    #[test]
+    fn test_pad_to_depth() {
+        let options = WriterOptions::builder()
+            .word_bytes(2)
+            .bytes_per_line(4)
+            .pad_to_depth(4, 0xFF)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options.clone());
+        writer.write_slice(&[0x01, 0x02, 0x03]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000000\n0201 FF03\nFFFF FFFF\n"
+        );
+
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.set_address(4);
+        writer.write_byte(0x05).unwrap();
+        assert_eq!(writer.finish().unwrap(), "@00000004\nFF05 FFFF\n");
+
+        let options = WriterOptions::builder()
+            .pad_to_depth(4, 0x00)
+            .fill_gaps(Addr::MAX, 0x00)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_bytes([(0, 0xAA), (2, 0xBB)]).unwrap();
+        assert_eq!(writer.finish().unwrap(), "@00000000\nAA 00 BB 00\n");
+    }
+
+    #[test]
+    fn test_bytes_per_line() {
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                bytes_per_line: 4,
+                ..Default::default()
+            },
+        );
+        writer
+            .write_bytes((0..10).map(|i| (0x100 + i, i as u8)))
+            .unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000100\n00 01 02 03\n04 05 06 07\n08 09\n"
+        );
+    }
+
+    #[test]
+    fn test_lowercase() {
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                hex_case: HexCase::Lower,
+                ..Default::default()
+            },
+        );
+        writer
+            .write_bytes([(0x8100ABCD, 0xEF), (0x8100ABCE, 0x0A)])
+            .unwrap();
+        assert_eq!(writer.finish().unwrap(), "@8100abcd\nef 0a\n");
+    }
+
+    #[test]
+    fn test_word_bytes() {
+        let bytes = || (0..6).map(|i| (0x100 + i, 0x10 + i as u8));
+
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                word_bytes: 4,
+                ..Default::default()
+            },
+        );
+        writer.write_bytes(bytes()).unwrap();
+        assert_eq!(writer.finish().unwrap(), "@00000100\n13121110 1514\n");
+
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions {
+                word_bytes: 4,
+                endian: Endian::Big,
+                ..Default::default()
+            },
+        );
+        writer.write_bytes(bytes()).unwrap();
+        assert_eq!(writer.finish().unwrap(), "@00000100\n10111213 14150000\n");
+
+        // 6 bytes are not a multiple of the word size, the padding takes the
+        // addresses of the missing bytes, so data after it starts a new segment.
+        let mut writer = Writer::new_with_options(
+            String::new(),
+            WriterOptions::builder()
+                .word_bytes(4)
+                .endian(Endian::Big)
+                .fill_gaps(0, 0xEE)
+                .build(),
+        );
+        writer.write_bytes(bytes()).unwrap();
+        writer.write_comment("end").unwrap();
+        writer.write_bytes([(0x106, 0x16)]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000100\n10111213 1415EEEE\n// end\n@00000106\n16EEEEEE\n"
+        );
+    }
+
+    #[test]
+    fn test_wide_words() {
+        let bytes = || (0..40).map(|i| (i, i as u8));
+
+        let options = WriterOptions::builder().word_bytes(32).build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_bytes(bytes()).unwrap();
+        let text = writer.finish().unwrap();
+        let mut lines = text.lines().skip(1);
+        assert_eq!(
+            lines.next(),
+            Some("1F1E1D1C1B1A191817161514131211100F0E0D0C0B0A09080706050403020100")
+        );
+        assert_eq!(lines.next(), Some("2726252423222120"));
+
+        let options = WriterOptions::builder()
+            .word_bytes(16)
+            .endian(Endian::Big)
+            .bytes_per_line(32)
+            .build();
+        let mut writer = Writer::new_with_options(String::new(), options);
+        writer.write_bytes(bytes()).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "@00000000\n\
+             000102030405060708090A0B0C0D0E0F 101112131415161718191A1B1C1D1E1F\n\
+             20212223242526270000000000000000\n"
+        );
+    }
+
+    #[test]
+    fn test_fixed_buffer_sink() {
+        struct Buffer {
+            data: [u8; 16],
+            len: usize,
+        }
+
+        impl fmt::Write for Buffer {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let end = self.len + s.len();
+                self.data
+                    .get_mut(self.len..end)
+                    .ok_or(fmt::Error)?
+                    .copy_from_slice(s.as_bytes());
+                self.len = end;
+                Ok(())
+            }
+        }
+
+        let mut writer = Writer::new(Buffer {
+            data: [0; 16],
+            len: 0,
+        });
+        writer.write_bytes([(0x10, 0xAB)]).unwrap();
+        let buffer = writer.finish().unwrap();
+        assert_eq!(&buffer.data[..buffer.len], b"@00000010\nAB\n");
+
+        let mut writer = Writer::new(Buffer {
+            data: [0; 16],
+            len: 0,
+        });
+        assert!(
+            writer
+                .write_bytes((0..8).map(|i| (0x10 + i, 0xAB)))
+                .and_then(|_| writer.finish().map(|_| ()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_lane_writer() {
+        let mut writer = LaneWriter::new([(); 4].map(|_| String::new()));
+        writer
+            .write_bytes((0..8).map(|i| (0x100 + i, 0x10 + i as u8)))
+            .unwrap();
+        writer.write_bytes([(0x201, 0xAA)]).unwrap();
+        let lanes = writer.finish().unwrap();
+        assert_eq!(lanes[0], "@00000040\n10 14\n");
+        assert_eq!(lanes[1], "@00000040\n11 15\n@00000080\nAA\n");
+        assert_eq!(lanes[2], "@00000040\n12 16\n");
+        assert_eq!(lanes[3], "@00000040\n13 17\n");
+    }
+
+    #[test]
+    fn test_transcode() {
+        let options = WriterOptions::builder()
+            .word_bytes(4)
+            .bytes_per_line(8)
+            .addr_offset(-0x71000000)
+            .build();
+        let text = transcode(
+            crate::Reader::new("@71000000 09 A0 F3 22 20 34 63 84 02\n@71000010 AA"),
+            Writer::new_with_options(String::new(), options.clone()),
+        );
+        assert_eq!(
+            text,
+            Ok("@00000000\n22F3A009 84633420\n02\n@00000010\nAA\n".into())
+        );
+
+        let text = transcode(
+            crate::Reader::new("@71000000 09 XY"),
+            Writer::new_with_options(String::new(), options),
+        );
+        let Err(TranscodeError::Reader(error)) = text else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(error.kind, crate::ReaderError::BadNumberConversion);
+        assert_eq!((error.line, error.column, error.token()), (1, 14, "XY"));
+
+        let options = crate::ReaderOptions {
+            data_lines: true,
+            token_bytes: 2,
+            ..Default::default()
+        };
+        let text = transcode(
+            crate::Reader::new_with_options("@10\nA009 22F3\n  3420 /**/ 8463", options.clone()),
+            Writer::new(String::new()),
+        );
+        assert_eq!(text, Ok("@00000010\n09 A0 F3 22 20 34 63 84\n".into()));
+
+        let text = transcode(
+            crate::Reader::new_with_options("@10\nA009\n  3420 84XY", options),
+            Writer::new(String::new()),
+        );
+        let Err(TranscodeError::Reader(error)) = text else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((error.line, error.column, error.token()), (3, 8, "84XY"));
+
+        let options = crate::ReaderOptions {
+            xz_digits: true,
+            ..Default::default()
+        };
+        let text = transcode(
+            crate::Reader::new_with_options("@10 0A 1x", options),
+            Writer::new(String::new()),
+        );
+        assert_eq!(text, Err(TranscodeError::Unknown { addr: 0x11 }));
+    }
+
+    #[test]
+    fn test_shard_writer() {
+        let mut writer = ShardWriter::new(1024, Default::default()).unwrap();
+        writer
+            .write_bytes((0..1000).map(|i| (0x81000000 + i, i as u8)))
+            .unwrap();
+        writer.write_bytes([(0x82000000, 0xAA)]).unwrap();
+        let shards = writer.finish().unwrap();
+        // The shard count depends on the width of `Addr`, only the bounds are checked.
+        assert_eq!(shards[0].addr, 0x81000000);
+
+        let mut bytes = std::vec::Vec::new();
+        for shard in &shards {
+            assert!(shard.text.len() <= 1024);
+            assert!(
+                shard
+                    .text
+                    .starts_with(&std::format!("@{:08X}\n", shard.addr))
+            );
+            for record in crate::Reader::new(&shard.text) {
+                if let Record::Data {
+                    addr,
+                    value: crate::DataType::U8(value),
+                } = record.unwrap()
+                {
+                    bytes.push((addr, value));
+                }
+            }
+        }
+        assert_eq!(bytes.len(), 1001);
+        assert_eq!(bytes[999], (0x81000000 + 999, 999u32 as u8));
+        assert_eq!(bytes[1000], (0x82000000, 0xAA));
+    }
+
+    #[test]
+    fn test_shard_writer_wide_words() {
+        let options = WriterOptions::builder()
+            .word_bytes(32)
+            .address_comments(true)
+            .checksum(ChecksumAlgorithm::Crc32)
+            .pad_to_depth(16, 0xFF)
+            .build();
+        assert!(matches!(
+            ShardWriter::new(100, options.clone()),
+            Err(WriterError::ShardTooSmall { .. })
+        ));
+        let mut writer = ShardWriter::new(300, options).unwrap();
+        writer.write_bytes((0..300).map(|i| (i, i as u8))).unwrap();
+        let shards = writer.finish().unwrap();
+        let mut digits = 0;
+        for shard in &shards {
+            assert!(shard.text.len() <= 300);
+            for line in shard
+                .text
+                .lines()
+                .filter(|line| !line.starts_with(['@', '/']))
+            {
+                digits += line.split("//").next().unwrap().trim().len();
+            }
+        }
+        // 300 bytes of data and 212 bytes of padding up to 16 words of 32 bytes.
+        assert_eq!(digits, 2 * 512);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let text = "@00000020\n0a 0b\n@00000010\n01 02 03\n@00000013\n04\n@00000011\nFF";
+        assert_eq!(
+            normalize(text),
+            Ok("@00000010\n01 FF 03 04\n@00000020\n0A 0B\n".into())
+        );
+        let error = normalize("@1\nXY").unwrap_err();
+        assert_eq!(
+            std::string::ToString::to_string(&error),
+            "2:1: cant convert string to number at `XY`"
+        );
+    }
+
+    #[test]
+    fn test_io_writer() {
+        let mut writer = Writer::new(IoWriter::new(std::vec::Vec::new()));
+        writer.write_bytes([(0x10, 0xAB), (0x11, 0xCD)]).unwrap();
+        let bytes = writer.finish().unwrap().into_inner().unwrap();
+        assert_eq!(bytes, b"@00000010\nAB CD\n");
+
+        struct Failing;
+
+        impl std::io::Write for Failing {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::StorageFull.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = Writer::new(IoWriter::new(Failing));
+        writer.write_bytes([(0x10, 0xAB)]).unwrap();
+        let error = writer.finish().unwrap().into_inner().err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::StorageFull);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let records = crate::Reader::new_with_options(
+            crate::TEXT_STR,
+            crate::ReaderOptions {
+                group: true,
+                ..Default::default()
+            },
+        )
+        .map(Result::unwrap);
+        let mut writer = Writer::new(String::new());
+        writer.write_records(records).unwrap();
+        let text = writer.finish().unwrap();
+
+        let data = |text| {
+            crate::Reader::new(text)
+                .map(Result::unwrap)
+                .filter(|record| matches!(record, Record::Data { .. }))
+        };
+        assert!(data(&text).eq(data(crate::TEXT_STR)));
+    }
+}